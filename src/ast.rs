@@ -1,4 +1,4 @@
-use std::{fmt::Display, collections::HashMap};
+use std::{fmt::Display, collections::HashMap, rc::Rc};
 
 use regex::Regex;
 
@@ -19,6 +19,7 @@ pub struct PatternBlock {
 #[derive(Debug)]
 pub enum Pattern {
     Expression(Expression),
+    Range { start: Expression, end: Expression },
     Begin,
     End,
 }
@@ -38,6 +39,7 @@ pub struct Block {
 #[derive(Debug, Clone)]
 pub enum Statement {
     PrintStatement(PrintStatement),
+    PrintfStatement(PrintfStatement),
     ExpressionStatement(Expression),
     BlockStatement(Block),
     LocalVarStatement {
@@ -53,13 +55,33 @@ pub enum Statement {
         condition: Expression,
         body: Box<Statement>,
     },
+    DoWhileStatement {
+        body: Box<Statement>,
+        condition: Expression,
+    },
     ForStatement {
         init_clause: Option<InitClause>,
         condition_expression: Option<Expression>,
         iteration_expression: Option<Expression>,
         body: Box<Statement>,
     },
+    ForInStatement {
+        key: Id,
+        array: Id,
+        body: Box<Statement>,
+    },
     ReturnStatement(Expression),
+    DeleteStatement {
+        array: Id,
+        // Deletes the entire array if there are no subscripts
+        subscripts: Option<Vec<Expression>>,
+    },
+    NextStatement,
+    NextfileStatement,
+    // Contains the optional exit status
+    ExitStatement(Option<Expression>),
+    BreakStatement,
+    ContinueStatement,
 }
 
 #[derive(Debug, Clone)]
@@ -71,22 +93,60 @@ pub enum InitClause {
 
 #[derive(Debug, Clone)]
 pub struct PrintStatement {
-    pub expression: Expression,
+    /// An empty list prints the current record
+    pub expressions: Vec<Expression>,
+    pub redirection: Option<OutputRedirection>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PrintfStatement {
+    pub format: Expression,
+    pub arguments: Vec<Expression>,
+    pub redirection: Option<OutputRedirection>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputRedirection {
+    pub kind: RedirectionKind,
+    /// The file name or command to send output to
+    pub target: Expression,
+}
+
+/// How the output of a `print` or `printf` statement is sent somewhere other than stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectionKind {
+    /// `> file`, which truncates the file when it's first opened
+    Truncate,
+    /// `>> file`
+    Append,
+    /// `| command`
+    Pipe,
 }
 
 #[derive(Debug, Clone)]
 pub enum Expression {
     String { value: String },
-    ColumnNumber(u32),
+    ColumnNumber(Box<Expression>),
     VarLookup(Id),
+    SpecialVarLookup(SpecialVar),
+    ArrayLookup {
+        id: Id,
+        subscripts: Vec<Expression>,
+    },
     Num(f64), // In awk, all numbers are floats
-    Regex(Regex),
+    // Shared rather than cloned when used as a field separator or regex argument, like cached regexes
+    Regex(Rc<Regex>),
 
     Plus(Box<Expression>, Box<Expression>),
     Minus(Box<Expression>, Box<Expression>),
     Times(Box<Expression>, Box<Expression>),
     Div(Box<Expression>, Box<Expression>),
     Modulo(Box<Expression>, Box<Expression>),
+    Pow(Box<Expression>, Box<Expression>),
+
+    LogicalNot(Box<Expression>),
+    UnaryMinus(Box<Expression>),
+    UnaryPlus(Box<Expression>),
 
     Concatenate(Box<Expression>, Box<Expression>),
 
@@ -97,8 +157,18 @@ pub enum Expression {
     GreaterThan(Box<Expression>, Box<Expression>),
     GreaterEqual(Box<Expression>, Box<Expression>),
 
-    Assign(Id, Box<Expression>),
-    PlusAssign(Id, Box<Expression>),
+    Assign(LValue, Box<Expression>),
+    PlusAssign(LValue, Box<Expression>),
+    MinusAssign(LValue, Box<Expression>),
+    TimesAssign(LValue, Box<Expression>),
+    DivAssign(LValue, Box<Expression>),
+    ModuloAssign(LValue, Box<Expression>),
+    PowAssign(LValue, Box<Expression>),
+
+    PreIncrement(LValue),
+    PreDecrement(LValue),
+    PostIncrement(LValue),
+    PostDecrement(LValue),
 
     RegexMatch(Box<Expression>, Box<Expression>),
     RegexNotMatch(Box<Expression>, Box<Expression>),
@@ -110,6 +180,96 @@ pub enum Expression {
 
     LogicalAnd(Box<Expression>, Box<Expression>),
     LogicalOr(Box<Expression>, Box<Expression>),
+
+    Conditional {
+        condition: Box<Expression>,
+        true_expression: Box<Expression>,
+        false_expression: Box<Expression>,
+    },
+
+    In {
+        subscripts: Vec<Expression>,
+        array: Id,
+    },
+
+    Getline {
+        source: GetlineSource,
+        // Reads into the current record when there is no lvalue
+        lvalue: Option<LValue>,
+    },
+}
+
+/// Where `getline` reads its record from
+#[derive(Debug, Clone)]
+pub enum GetlineSource {
+    MainInput,
+    // Contains the expression for the file name
+    File(Box<Expression>),
+    // Contains the expression for the command
+    Command(Box<Expression>),
+}
+
+/// Something which can be assigned to
+#[derive(Debug, Clone)]
+pub enum LValue {
+    Variable(Id),
+    Special(SpecialVar),
+    ArrayElement { id: Id, subscripts: Vec<Expression> },
+    // Contains the expression for the column number
+    Column(Box<Expression>),
+}
+
+impl LValue {
+    /// Returns the lvalue for the variable with the given name, which may be a special variable
+    pub fn variable(name: &str) -> LValue {
+        match SpecialVar::from_name(name) {
+            Some(special_var) => LValue::Special(special_var),
+            None => LValue::Variable(Id(name.to_string())),
+        }
+    }
+}
+
+/// A variable with a special meaning to awk. These are found by name when the program is parsed,
+/// so that the interpreter doesn't need to compare names whenever a variable is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecialVar {
+    Fs,
+    Ofs,
+    Ors,
+    Rs,
+    // The text that terminated the current record, which depends on RS (as in gawk)
+    Rt,
+    Nr,
+    Fnr,
+    // The number of fields, which is computed from the current record instead of being stored
+    Nf,
+    Filename,
+    Subsep,
+    Convfmt,
+    Ofmt,
+}
+
+impl SpecialVar {
+    /// Returns the special variable with the given name, if there is one
+    pub fn from_name(name: &str) -> Option<SpecialVar> {
+        let special_var = match name {
+            "FS" => SpecialVar::Fs,
+            "OFS" => SpecialVar::Ofs,
+            "ORS" => SpecialVar::Ors,
+            "RS" => SpecialVar::Rs,
+            "RT" => SpecialVar::Rt,
+            "NR" => SpecialVar::Nr,
+            "FNR" => SpecialVar::Fnr,
+            "NF" => SpecialVar::Nf,
+            "FILENAME" => SpecialVar::Filename,
+            "SUBSEP" => SpecialVar::Subsep,
+            "CONVFMT" => SpecialVar::Convfmt,
+            "OFMT" => SpecialVar::Ofmt,
+            _ => return None,
+        };
+
+        Some(special_var)
+    }
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
@@ -1,6 +1,5 @@
-use chawk::Interpreter;
+use chawk::{unescape_string, Interpreter};
 use std::{
-    collections::HashMap,
     fs::{self, File},
     io::{self, BufRead, BufReader, Write},
     process::exit,
@@ -11,7 +10,9 @@ use clap::{arg, command};
 fn main() {
     let mut command_cli = command!()
         .arg(arg!([argument]).multiple_occurrences(true))
-        .arg(arg!(-f <progfile>).required(false));
+        .arg(arg!(-f <progfile>).required(false))
+        .arg(arg!(-F <fs>).required(false))
+        .arg(arg!(--csv));
 
     // Store help text before obtaining matches, which consumes command_cli
     let mut help_text = Vec::new();
@@ -44,22 +45,29 @@ fn main() {
         program_text.to_string()
     };
 
+    let mut interpreter = Interpreter::new();
+
+    // Like a string literal in the program, the field separator can use escape sequences, as in
+    // `-F '\t'`
+    if let Some(field_separator) = matches.value_of("fs") {
+        interpreter.set_variable("FS", &unescape_string(field_separator));
+    }
+
+    if matches.is_present("csv") {
+        interpreter.enable_csv_mode();
+    }
+
     // Obtain the input for the records (file vs stdin)
-    let mut records_reader: Box<dyn BufRead> = if positional_arguments.is_empty() {
+    let records_reader: Box<dyn BufRead> = if positional_arguments.is_empty() {
         let stdin = io::stdin();
         Box::new(BufReader::new(stdin))
     } else {
-        let file = File::open(&positional_arguments[0]).expect("Cannot read records file");
+        let file = File::open(positional_arguments[0]).expect("Cannot read records file");
+        interpreter.set_variable("FILENAME", positional_arguments[0]);
         Box::new(BufReader::new(file))
     };
 
-    let mut interpreter = Interpreter {
-        curr_columns: vec![],
-        curr_line: String::new(),
-        global_vars: HashMap::new(),
-        local_vars: vec![],
-        function_defs: HashMap::new(),
-    };
+    let exit_code = interpreter.run(&unparsed_file, records_reader);
 
-    interpreter.run(&unparsed_file, &mut records_reader);
+    exit(exit_code);
 }
@@ -56,7 +56,7 @@ fn test_awk_file(awk_input_file: &str) -> Result<()> {
     let desired_output_file = format!("{}.output", awk_input_file);
     let desired_output_path = Path::new(&desired_output_file);
     let unparsed_desired_output_file =
-        fs::read_to_string(&desired_output_path).with_context(|| "Failed to open output file")?;
+        fs::read_to_string(desired_output_path).with_context(|| "Failed to open output file")?;
     let desired_outputs = parse_output_file(&unparsed_desired_output_file);
 
     for desired_output in &desired_outputs {
@@ -71,8 +71,9 @@ fn test_awk_file(awk_input_file: &str) -> Result<()> {
         // to have the most recently-built version of the interpreter.
         let mut chawk_command = Command::new("target/debug/chawk");
         chawk_command
+            .args(&desired_output.flags)
             .arg("-f")
-            .arg(&awk_input_file)
+            .arg(awk_input_file)
             .arg(&data_file_path);
 
         // println!("Running {:?}", chawk_command);
@@ -145,6 +146,9 @@ fn reset_color() {
 
 struct DesiredOutput {
     data_file_name: String,
+    // Command-line flags to run chawk with, which follow the data file name in the header, as in
+    // `{{ people.csv --csv }}`
+    flags: Vec<String>,
     output: String,
 }
 
@@ -153,9 +157,11 @@ fn parse_output_file(source: &str) -> Vec<DesiredOutput> {
 
     for line in source.lines() {
         if line.starts_with("{{ ") && line.ends_with(" }}") {
-            let data_file_name = line[3..line.len() - 3].to_string();
+            let mut header_words = line[3..line.len() - 3].split_whitespace().map(String::from);
+            let data_file_name = header_words.next().expect("No data file name in header");
             result.push(DesiredOutput {
                 data_file_name,
+                flags: header_words.collect(),
                 output: String::new(),
             });
         } else {
@@ -0,0 +1,131 @@
+// Splits records into fields, following the POSIX rules for the field separator (FS). The same
+// rules are used by the `split` built-in function.
+
+use std::rc::Rc;
+
+use regex::Regex;
+
+use crate::regex_cache::RegexCache;
+
+/// How a record is split into fields, as determined by the value of FS
+#[derive(Debug, Clone, Default)]
+pub enum FieldSplitter {
+    /// FS is a single space: fields are separated by runs of blanks and newlines, and leading and
+    /// trailing blanks and newlines are ignored
+    #[default]
+    Whitespace,
+    /// FS is any other single character, which separates fields literally
+    Char(char),
+    /// FS is longer than one character, so it's used as an extended regular expression
+    Regex(Rc<Regex>),
+    /// FS is empty, so every character is a field of its own (as in gawk and mawk)
+    Chars,
+    /// In CSV mode, fields are separated by commas and may be quoted, as described in RFC 4180
+    Csv,
+}
+
+impl FieldSplitter {
+    /// Creates the splitter for the given field separator, which may be an invalid regex
+    pub fn new(separator: &str, regex_cache: &mut RegexCache) -> Result<Self, regex::Error> {
+        let mut chars = separator.chars();
+
+        let splitter = match (chars.next(), chars.next()) {
+            (None, _) => FieldSplitter::Chars,
+            (Some(' '), None) => FieldSplitter::Whitespace,
+            (Some(ch), None) => FieldSplitter::Char(ch),
+            _ => FieldSplitter::Regex(regex_cache.get(separator)?),
+        };
+
+        Ok(splitter)
+    }
+
+    /// Returns a splitter which also treats newlines as field separators, as needed when RS is
+    /// empty
+    pub fn also_splitting_on_newlines(self) -> Self {
+        let pattern = match &self {
+            // These already split on newlines, or split every character anyway
+            FieldSplitter::Whitespace
+            | FieldSplitter::Chars
+            | FieldSplitter::Csv
+            | FieldSplitter::Char('\n') => return self,
+            FieldSplitter::Char(separator) => {
+                format!("{}|\n", regex::escape(&separator.to_string()))
+            }
+            FieldSplitter::Regex(regex) => format!("(?:{})|\n", regex.as_str()),
+        };
+
+        FieldSplitter::Regex(Rc::new(
+            Regex::new(&pattern).expect("Separator was already a valid regex"),
+        ))
+    }
+
+    /// Splits a string into its fields. An empty string has no fields.
+    pub fn split(&self, string: &str) -> Vec<String> {
+        if string.is_empty() {
+            return vec![];
+        }
+
+        match self {
+            FieldSplitter::Whitespace => string
+                .split(is_field_blank)
+                .filter(|field| !field.is_empty())
+                .map(String::from)
+                .collect(),
+            FieldSplitter::Char(separator) => string.split(*separator).map(String::from).collect(),
+            FieldSplitter::Regex(regex) => regex.split(string).map(String::from).collect(),
+            FieldSplitter::Chars => string.chars().map(String::from).collect(),
+            FieldSplitter::Csv => split_csv(string),
+        }
+    }
+}
+
+/// Splits a CSV record into its fields, removing the quotes around quoted fields. Quotes that
+/// don't start a field are kept as they are.
+fn split_csv(string: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut chars = string.chars().peekable();
+    let mut in_quotes = false;
+    let mut at_field_start = true;
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            // Inside a quoted field, two quotes stand for a single quote
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if at_field_start => in_quotes = true,
+            ',' if !in_quotes => {
+                fields.push(std::mem::take(&mut field));
+                at_field_start = true;
+                continue;
+            }
+            _ => field.push(ch),
+        }
+
+        at_field_start = false;
+    }
+
+    fields.push(field);
+
+    fields
+}
+
+/// Quotes a field for CSV output, if it contains a comma, a quote or a line break
+pub fn quote_csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Returns whether a character separates fields when FS is a single space
+fn is_field_blank(ch: char) -> bool {
+    matches!(ch, ' ' | '\t' | '\n')
+}
@@ -0,0 +1,360 @@
+// Implements the printf-style formatting used by awk's `printf` statement and `sprintf` function.
+// This largely follows the conversion specifications from the POSIX printf utility
+// (https://pubs.opengroup.org/onlinepubs/9699919799/functions/printf.html).
+
+use std::{iter::Peekable, str::Chars};
+
+use crate::Value;
+
+/// Formats `args` according to the conversion specifications in `format`. Missing arguments are
+/// treated as empty strings, while extra arguments are ignored.
+pub fn sprintf(format: &str, args: &[Value]) -> String {
+    let mut result = String::new();
+    let mut args = args.iter();
+    let mut chars = format.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            result.push(ch);
+            continue;
+        }
+
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            result.push('%');
+            continue;
+        }
+
+        // Remember where the specification started, so that we can print it verbatim if it turns
+        // out to be invalid
+        let spec_start = chars.clone();
+
+        let mut spec = parse_flags(&mut chars);
+
+        if chars.peek() == Some(&'*') {
+            chars.next();
+            let width = next_arg(&mut args).to_num() as i64;
+            // A negative width from an argument is treated as the `-` flag with a positive width
+            if width < 0 {
+                spec.left_align = true;
+            }
+            spec.width = Some(width.unsigned_abs() as usize);
+        } else {
+            spec.width = parse_number(&mut chars);
+        }
+
+        if chars.peek() == Some(&'.') {
+            chars.next();
+
+            if chars.peek() == Some(&'*') {
+                chars.next();
+                let precision = next_arg(&mut args).to_num() as i64;
+                // A negative precision from an argument is taken as if it were omitted
+                spec.precision = if precision < 0 {
+                    None
+                } else {
+                    Some(precision as usize)
+                };
+            } else {
+                spec.precision = Some(parse_number(&mut chars).unwrap_or(0));
+            }
+        }
+
+        let conversion = match chars.next() {
+            Some(conversion) => conversion,
+            None => {
+                result.push('%');
+                result.extend(spec_start);
+                break;
+            }
+        };
+
+        let formatted = match conversion {
+            'd' | 'i' => format_signed(&spec, next_arg(&mut args).to_num()),
+            'o' | 'x' | 'X' | 'u' => {
+                format_unsigned(&spec, conversion, next_arg(&mut args).to_num())
+            }
+            'e' | 'E' | 'f' | 'F' | 'g' | 'G' => {
+                format_float(&spec, conversion, next_arg(&mut args).to_num())
+            }
+            'c' => {
+                let character = match next_arg(&mut args) {
+                    Value::Num(num) => char::from_u32(num as u32)
+                        .map(String::from)
+                        .unwrap_or_default(),
+                    value => value.to_string().chars().take(1).collect(),
+                };
+
+                pad(&spec, "", &character, false)
+            }
+            's' => {
+                let mut string = next_arg(&mut args).to_string();
+
+                if let Some(precision) = spec.precision {
+                    string = string.chars().take(precision).collect();
+                }
+
+                pad(&spec, "", &string, false)
+            }
+            _ => {
+                // Unknown conversions are printed as-is
+                result.push('%');
+                result.extend(spec_start.take_while(|ch| *ch != conversion));
+                result.push(conversion);
+                continue;
+            }
+        };
+
+        result.push_str(&formatted);
+    }
+
+    result
+}
+
+#[derive(Default)]
+struct Spec {
+    left_align: bool,
+    plus_sign: bool,
+    space_sign: bool,
+    alternate_form: bool,
+    zero_pad: bool,
+    width: Option<usize>,
+    precision: Option<usize>,
+}
+
+fn parse_flags(chars: &mut Peekable<Chars>) -> Spec {
+    let mut spec = Spec::default();
+
+    while let Some(ch) = chars.peek() {
+        match ch {
+            '-' => spec.left_align = true,
+            '+' => spec.plus_sign = true,
+            ' ' => spec.space_sign = true,
+            '#' => spec.alternate_form = true,
+            '0' => spec.zero_pad = true,
+            _ => break,
+        }
+
+        chars.next();
+    }
+
+    spec
+}
+
+fn parse_number(chars: &mut Peekable<Chars>) -> Option<usize> {
+    let mut number = None;
+
+    while let Some(digit) = chars.peek().and_then(|ch| ch.to_digit(10)) {
+        number = Some(number.unwrap_or(0) * 10 + digit as usize);
+        chars.next();
+    }
+
+    number
+}
+
+fn next_arg<'a>(args: &mut impl Iterator<Item = &'a Value>) -> Value {
+    args.next()
+        .cloned()
+        .unwrap_or_else(|| Value::String(String::new()))
+}
+
+fn sign_prefix(spec: &Spec, is_negative: bool) -> &'static str {
+    if is_negative {
+        "-"
+    } else if spec.plus_sign {
+        "+"
+    } else if spec.space_sign {
+        " "
+    } else {
+        ""
+    }
+}
+
+/// Pads `body` (which comes after `prefix`, such as a sign) to the width of the specification.
+/// Zero padding goes between the prefix and the body.
+fn pad(spec: &Spec, prefix: &str, body: &str, allow_zero_pad: bool) -> String {
+    let len = prefix.chars().count() + body.chars().count();
+    let width = spec.width.unwrap_or(0);
+
+    if len >= width {
+        return format!("{}{}", prefix, body);
+    }
+
+    let padding = width - len;
+
+    if spec.left_align {
+        format!("{}{}{}", prefix, body, " ".repeat(padding))
+    } else if spec.zero_pad && allow_zero_pad {
+        format!("{}{}{}", prefix, "0".repeat(padding), body)
+    } else {
+        format!("{}{}{}", " ".repeat(padding), prefix, body)
+    }
+}
+
+/// Applies the precision of an integer conversion, which is its minimum number of digits
+fn integer_digits(spec: &Spec, digits: String) -> String {
+    match spec.precision {
+        // Converting zero with a precision of zero produces no digits
+        Some(0) if digits == "0" => String::new(),
+        Some(precision) if digits.len() < precision => {
+            format!("{}{}", "0".repeat(precision - digits.len()), digits)
+        }
+        _ => digits,
+    }
+}
+
+fn format_signed(spec: &Spec, num: f64) -> String {
+    if !num.is_finite() {
+        return format_non_finite(spec, 'd', num);
+    }
+
+    let num = num.trunc();
+
+    // Numbers too large for an i64 are still printed as integers
+    let digits = if num.abs() < 9.2e18 {
+        (num as i64).unsigned_abs().to_string()
+    } else {
+        format!("{:.0}", num.abs())
+    };
+
+    let body = integer_digits(spec, digits);
+
+    pad(
+        spec,
+        sign_prefix(spec, num < 0.0),
+        &body,
+        spec.precision.is_none(),
+    )
+}
+
+fn format_unsigned(spec: &Spec, conversion: char, num: f64) -> String {
+    if !num.is_finite() {
+        return format_non_finite(spec, conversion, num);
+    }
+
+    // Like C, negative numbers wrap around
+    let num = num.trunc() as i64 as u64;
+
+    let (digits, prefix) = match conversion {
+        'o' => (format!("{:o}", num), ""),
+        'x' => (format!("{:x}", num), if num != 0 { "0x" } else { "" }),
+        'X' => (format!("{:X}", num), if num != 0 { "0X" } else { "" }),
+        _ => (num.to_string(), ""),
+    };
+
+    let mut body = integer_digits(spec, digits);
+
+    let prefix = if spec.alternate_form { prefix } else { "" };
+
+    // The alternate form for octal guarantees a leading zero
+    if spec.alternate_form && conversion == 'o' && !body.starts_with('0') {
+        body.insert(0, '0');
+    }
+
+    pad(spec, prefix, &body, spec.precision.is_none())
+}
+
+fn format_float(spec: &Spec, conversion: char, num: f64) -> String {
+    if !num.is_finite() {
+        return format_non_finite(spec, conversion, num);
+    }
+
+    let precision = spec.precision.unwrap_or(6);
+
+    let mut body = match conversion {
+        'e' | 'E' => format_exponential(num.abs(), precision, spec.alternate_form),
+        'f' | 'F' => format_fixed(num.abs(), precision, spec.alternate_form),
+        _ => format_general(num.abs(), precision, spec.alternate_form),
+    };
+
+    if conversion.is_ascii_uppercase() {
+        body = body.to_uppercase();
+    }
+
+    // Checking the sign bit means that -0 is printed as "-0", as in C
+    pad(spec, sign_prefix(spec, num.is_sign_negative()), &body, true)
+}
+
+fn format_non_finite(spec: &Spec, conversion: char, num: f64) -> String {
+    let mut body = if num.is_nan() { "nan" } else { "inf" }.to_string();
+
+    if conversion.is_ascii_uppercase() {
+        body = body.to_uppercase();
+    }
+
+    pad(
+        spec,
+        sign_prefix(spec, num.is_sign_negative()),
+        &body,
+        false,
+    )
+}
+
+fn format_fixed(num: f64, precision: usize, alternate_form: bool) -> String {
+    let mut result = format!("{:.*}", precision, num);
+
+    // The alternate form always has a decimal point
+    if alternate_form && precision == 0 {
+        result.push('.');
+    }
+
+    result
+}
+
+/// Splits a number into its mantissa (rounded to `precision` decimal places) and its exponent
+fn split_exponential(num: f64, precision: usize) -> (String, i32) {
+    let formatted = format!("{:.*e}", precision, num);
+    let (mantissa, exponent) = formatted
+        .split_once('e')
+        .expect("Exponential format has an exponent");
+
+    (
+        mantissa.to_string(),
+        exponent.parse().expect("Exponent is an integer"),
+    )
+}
+
+fn format_exponential(num: f64, precision: usize, alternate_form: bool) -> String {
+    let (mut mantissa, exponent) = split_exponential(num, precision);
+
+    if alternate_form && precision == 0 {
+        mantissa.push('.');
+    }
+
+    // C always uses a sign and at least two digits for the exponent
+    let exponent_sign = if exponent < 0 { '-' } else { '+' };
+
+    format!("{}e{}{:02}", mantissa, exponent_sign, exponent.abs())
+}
+
+fn format_general(num: f64, precision: usize, alternate_form: bool) -> String {
+    // A precision of 0 is treated as 1 for %g
+    let precision = precision.max(1);
+
+    // The exponent we need is that of the number after rounding it to `precision` significant
+    // figures
+    let (_, exponent) = split_exponential(num, precision - 1);
+
+    let mut result = if exponent < -4 || exponent >= precision as i32 {
+        format_exponential(num, precision - 1, alternate_form)
+    } else {
+        format_fixed(
+            num,
+            (precision as i32 - 1 - exponent) as usize,
+            alternate_form,
+        )
+    };
+
+    // Unlike the other conversions, %g removes trailing zeros (unless using the alternate form)
+    if !alternate_form {
+        let exponent_start = result.find('e').unwrap_or(result.len());
+        let (digits, exponent_part) = result.split_at(exponent_start);
+
+        if digits.contains('.') {
+            let trimmed = digits.trim_end_matches('0').trim_end_matches('.');
+            result = format!("{}{}", trimmed, exponent_part);
+        }
+    }
+
+    result
+}
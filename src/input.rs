@@ -0,0 +1,383 @@
+// Keeps track of the sources that records can be read from: the main input, along with the files
+// and commands that `getline` can read from.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    process::{Child, ChildStdout, Command, Stdio},
+};
+
+use crate::regex_cache::RegexCache;
+
+/// How the input is split into records, as determined by the value of RS
+#[derive(Debug, Clone)]
+pub enum RecordSeparator {
+    /// RS is a single (ASCII) character, which terminates each record
+    Byte(u8),
+    /// RS is empty, so records are separated by one or more blank lines
+    Paragraph,
+    /// RS is longer than one character, so it's used as an extended regular expression
+    Regex(regex::bytes::Regex),
+    /// In CSV mode, records end at newlines, unless the newline is inside a quoted field
+    Csv,
+}
+
+impl Default for RecordSeparator {
+    fn default() -> Self {
+        RecordSeparator::Byte(b'\n')
+    }
+}
+
+impl RecordSeparator {
+    /// Creates the record separator for the given value of RS, which may be an invalid regex
+    pub fn new(separator: &str, regex_cache: &mut RegexCache) -> Result<Self, regex::Error> {
+        let record_separator = match separator.as_bytes() {
+            [] => RecordSeparator::Paragraph,
+            [separator_byte] => RecordSeparator::Byte(*separator_byte),
+            _ => {
+                // The regex cache takes care of awk's escape sequences and reporting invalid
+                // regexes, but the input is matched as bytes, since it may not be valid UTF-8 yet
+                let regex = regex_cache.get(separator)?;
+
+                RecordSeparator::Regex(
+                    regex::bytes::Regex::new(regex.as_str()).expect("Regex was already compiled"),
+                )
+            }
+        };
+
+        Ok(record_separator)
+    }
+}
+
+/// A record, along with the text that terminated it (which is used for RT)
+pub struct Record {
+    pub text: String,
+    pub terminator: String,
+}
+
+enum InputStream {
+    File(BufReader<File>),
+    Command(Child, BufReader<ChildStdout>),
+}
+
+/// The main input, along with a table of the other open input streams, keyed by the file name or
+/// command used to open them
+pub struct InputSources {
+    main_input: Box<dyn BufRead>,
+    streams: HashMap<String, InputStream>,
+}
+
+impl Default for InputSources {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputSources {
+    /// Creates the input sources with an empty main input
+    pub fn new() -> Self {
+        InputSources {
+            main_input: Box::new(io::empty()),
+            streams: HashMap::new(),
+        }
+    }
+
+    pub fn set_main_input(&mut self, main_input: Box<dyn BufRead>) {
+        self.main_input = main_input;
+    }
+
+    /// Reads the next record from the main input, returning None at the end of the input
+    pub fn read_main_record(&mut self, separator: &RecordSeparator) -> io::Result<Option<Record>> {
+        read_record(&mut *self.main_input, separator)
+    }
+
+    /// Skips the rest of the main input. Since the main input is a single file (or stdin), this is
+    /// what `nextfile` does.
+    pub fn skip_main_file(&mut self) {
+        self.main_input = Box::new(io::empty());
+    }
+
+    /// Reads the next record from the given file, opening it if it isn't already open
+    pub fn read_file_record(
+        &mut self,
+        name: &str,
+        separator: &RecordSeparator,
+    ) -> io::Result<Option<Record>> {
+        if !self.streams.contains_key(name) {
+            let file = File::open(name)?;
+            self.streams
+                .insert(name.to_string(), InputStream::File(BufReader::new(file)));
+        }
+
+        self.read_stream_record(name, separator)
+    }
+
+    /// Reads the next record from the output of the given command, running it if it isn't already
+    /// running
+    pub fn read_command_record(
+        &mut self,
+        command: &str,
+        separator: &RecordSeparator,
+    ) -> io::Result<Option<Record>> {
+        if !self.streams.contains_key(command) {
+            let mut child = Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .stdout(Stdio::piped())
+                .spawn()?;
+            let stdout = child.stdout.take().expect("Command has no stdout");
+
+            self.streams.insert(
+                command.to_string(),
+                InputStream::Command(child, BufReader::new(stdout)),
+            );
+        }
+
+        self.read_stream_record(command, separator)
+    }
+
+    fn read_stream_record(
+        &mut self,
+        name: &str,
+        separator: &RecordSeparator,
+    ) -> io::Result<Option<Record>> {
+        match self.streams.get_mut(name).expect("Stream was just opened") {
+            InputStream::File(reader) => read_record(reader, separator),
+            InputStream::Command(_, reader) => read_record(reader, separator),
+        }
+    }
+
+    /// Closes the stream with the given name. Returns -1 if it isn't open, the exit status of the
+    /// command for a command, and 0 otherwise.
+    pub fn close(&mut self, name: &str) -> i32 {
+        match self.streams.remove(name) {
+            Some(stream) => Self::close_stream(stream),
+            None => -1,
+        }
+    }
+
+    /// Closes every stream, such as when the program exits
+    pub fn close_all(&mut self) {
+        for (_, stream) in self.streams.drain() {
+            Self::close_stream(stream);
+        }
+    }
+
+    fn close_stream(stream: InputStream) -> i32 {
+        match stream {
+            InputStream::File(_) => 0,
+            InputStream::Command(mut child, reader) => {
+                // The command may still be writing output that we'll never read
+                drop(reader);
+
+                match child.wait() {
+                    Ok(status) => status.code().unwrap_or(-1),
+                    Err(_) => -1,
+                }
+            }
+        }
+    }
+}
+
+/// Reads the next record, returning None at the end of the input
+fn read_record(
+    reader: &mut dyn BufRead,
+    separator: &RecordSeparator,
+) -> io::Result<Option<Record>> {
+    let record = match separator {
+        RecordSeparator::Byte(separator_byte) => read_terminated_record(reader, *separator_byte)?,
+        RecordSeparator::Paragraph => read_paragraph(reader)?,
+        RecordSeparator::Regex(regex) => read_regex_record(reader, regex)?,
+        RecordSeparator::Csv => read_csv_record(reader)?,
+    };
+
+    match record {
+        Some((text, terminator)) => Ok(Some(Record {
+            text: bytes_to_string(text)?,
+            terminator: bytes_to_string(terminator)?,
+        })),
+        None => Ok(None),
+    }
+}
+
+// Each of the following functions returns the bytes of the record and its terminator
+
+fn read_terminated_record(
+    reader: &mut dyn BufRead,
+    separator_byte: u8,
+) -> io::Result<Option<(Vec<u8>, Vec<u8>)>> {
+    let mut record = vec![];
+
+    if reader.read_until(separator_byte, &mut record)? == 0 {
+        return Ok(None);
+    }
+
+    let terminator = split_off_terminator(&mut record, separator_byte);
+
+    Ok(Some((record, terminator)))
+}
+
+/// Removes the separator from the end of a record (if it's there), returning it
+fn split_off_terminator(record: &mut Vec<u8>, separator_byte: u8) -> Vec<u8> {
+    let mut terminator = vec![];
+
+    if record.last() == Some(&separator_byte) {
+        terminator.insert(0, record.pop().expect("Record ends with the separator"));
+
+        // Like BufRead::lines, we strip both "\n" and "\r\n"
+        if separator_byte == b'\n' && record.last() == Some(&b'\r') {
+            terminator.insert(0, record.pop().expect("Record ends with a carriage return"));
+        }
+    }
+
+    terminator
+}
+
+fn read_csv_record(reader: &mut dyn BufRead) -> io::Result<Option<(Vec<u8>, Vec<u8>)>> {
+    let mut record = vec![];
+
+    // An odd number of quotes means that the newline is inside a quoted field, since a quote
+    // inside a quoted field is written as two quotes
+    while reader.read_until(b'\n', &mut record)? > 0
+        && record.iter().filter(|&&b| b == b'"').count() % 2 == 1
+    {}
+
+    if record.is_empty() {
+        return Ok(None);
+    }
+
+    let terminator = split_off_terminator(&mut record, b'\n');
+
+    Ok(Some((record, terminator)))
+}
+
+/// Reads a record that ends at a blank line (or the end of the input), skipping any blank lines
+/// before it
+fn read_paragraph(reader: &mut dyn BufRead) -> io::Result<Option<(Vec<u8>, Vec<u8>)>> {
+    skip_newlines(reader, &mut vec![])?;
+
+    let mut record = vec![];
+
+    // Read whole lines until the record ends with an empty line
+    while reader.read_until(b'\n', &mut record)? > 0 && !record.ends_with(b"\n\n") {}
+
+    if record.is_empty() {
+        return Ok(None);
+    }
+
+    // The terminator is the whole run of newlines after the record
+    let text_len = record.len() - record.iter().rev().take_while(|&&b| b == b'\n').count();
+    let mut terminator = record.split_off(text_len);
+    skip_newlines(reader, &mut terminator)?;
+
+    Ok(Some((record, terminator)))
+}
+
+/// Consumes the newlines at the start of the reader's input, appending them to `skipped`
+fn skip_newlines(reader: &mut dyn BufRead, skipped: &mut Vec<u8>) -> io::Result<()> {
+    loop {
+        let available = reader.fill_buf()?;
+        let num_newlines = available.iter().take_while(|&&b| b == b'\n').count();
+
+        if num_newlines == 0 {
+            return Ok(());
+        }
+
+        skipped.extend_from_slice(&available[..num_newlines]);
+        reader.consume(num_newlines);
+    }
+}
+
+/// Reads a record that ends at a match of the regex. The match is searched for in the reader's
+/// buffer, so the input is only copied more than once when a record is longer than the buffer.
+fn read_regex_record(
+    reader: &mut dyn BufRead,
+    regex: &regex::bytes::Regex,
+) -> io::Result<Option<(Vec<u8>, Vec<u8>)>> {
+    // The start of the record, from earlier buffers which had no complete match
+    let mut record = vec![];
+    // Where to start searching the record, since there's no match before this
+    let mut search_start = 0;
+
+    loop {
+        let available = reader.fill_buf()?;
+
+        if available.is_empty() {
+            if record.is_empty() {
+                return Ok(None);
+            }
+
+            // A match at the end of the input can't get any longer
+            let terminator = match find_separator(regex, &record, search_start) {
+                Some((start, _)) => record.split_off(start),
+                None => vec![],
+            };
+
+            return Ok(Some((record, terminator)));
+        }
+
+        let amount = available.len();
+
+        // A match that reaches the end of what we've read so far might be longer once we read
+        // more of the input, as with RS = "\n+". The next search starts from that match.
+        if record.is_empty() {
+            match find_separator(regex, available, 0) {
+                Some((start, end)) if end < amount => {
+                    let found = (available[..start].to_vec(), available[start..end].to_vec());
+                    reader.consume(end);
+
+                    return Ok(Some(found));
+                }
+                found => search_start = found.map_or(0, |(start, _)| start),
+            }
+
+            record.extend_from_slice(available);
+        } else {
+            let previous_len = record.len();
+            record.extend_from_slice(available);
+
+            match find_separator(regex, &record, search_start) {
+                Some((start, end)) if end < record.len() => {
+                    // There was no complete match in the earlier buffers, so only the part of
+                    // this buffer up to the end of the match is used
+                    reader.consume(end - previous_len);
+
+                    let terminator = record[start..end].to_vec();
+                    record.truncate(start);
+
+                    return Ok(Some((record, terminator)));
+                }
+                found => search_start = found.map_or(0, |(start, _)| start),
+            }
+        }
+
+        reader.consume(amount);
+    }
+}
+
+/// Returns the start and end of the first non-empty match at or after `search_start`, since empty
+/// matches can't separate records
+fn find_separator(
+    regex: &regex::bytes::Regex,
+    haystack: &[u8],
+    search_start: usize,
+) -> Option<(usize, usize)> {
+    let mut position = search_start;
+
+    while position <= haystack.len() {
+        let found = regex.find_at(haystack, position)?;
+
+        if found.end() > found.start() {
+            return Some((found.start(), found.end()));
+        }
+
+        position = found.end() + 1;
+    }
+
+    None
+}
+
+fn bytes_to_string(bytes: Vec<u8>) -> io::Result<String> {
+    String::from_utf8(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
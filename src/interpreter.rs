@@ -1,46 +1,134 @@
 use std::{
-    collections::HashMap,
+    cell::RefCell,
+    collections::{HashMap, HashSet},
     fmt::Display,
-    io::BufRead,
+    io::{self, BufRead},
     mem::swap,
     ops::{Add, Div, Mul, Rem, Sub},
+    process,
+    rc::Rc,
 };
 
 use crate::{
-    ast::{Block, Expression, Id, Pattern, PatternBlock, PrintStatement, Statement},
+    ast::{
+        Block, Expression, GetlineSource, Id, LValue, OutputRedirection, Pattern, PatternBlock,
+        PrintStatement, PrintfStatement, SpecialVar, Statement,
+    },
+    fields::{quote_csv_field, FieldSplitter},
+    format::sprintf,
+    input::{InputSources, Record, RecordSeparator},
+    output::OutputStreams,
+    random::Random,
+    regex_cache::RegexCache,
     InitClause, Program,
 };
 use crate::{parser::parse, FunctionDef};
 
+use self::{
+    builtins::lookup_builtin,
+    special_vars::{SpecialVars, DEFAULT_NUMBER_FORMAT},
+};
+
+mod builtins;
+mod special_vars;
+
 pub struct Interpreter {
     pub curr_columns: Vec<String>,
     pub curr_line: String,
     pub global_vars: HashMap<Id, Value>,
     pub local_vars: Vec<HashMap<Id, Value>>,
     pub function_defs: HashMap<Id, FunctionDef>,
+    special_vars: SpecialVars,
+    // Built from FS and RS whenever they're assigned to, rather than for every record
+    field_splitter: FieldSplitter,
+    record_separator: RecordSeparator,
+    // In CSV mode, FS and RS are ignored in favour of parsing the input as CSV
+    csv_mode: bool,
+    output_streams: OutputStreams,
+    input_sources: InputSources,
+    // Set when a function uses `next`, `nextfile` or `exit`, which can't be returned through an
+    // expression
+    pending_flow: Option<ControlFlow>,
+    // The indices of the pattern blocks whose range patterns have started but not yet ended
+    active_ranges: HashSet<usize>,
+    regex_cache: RegexCache,
+    random: Random,
+    exit_code: i32,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
-    pub fn run(&mut self, program_str: &str, records_reader: &mut dyn BufRead) {
-        let program_ast = parse(program_str).unwrap();
+    pub fn new() -> Self {
+        Interpreter {
+            curr_columns: vec![],
+            curr_line: String::new(),
+            global_vars: HashMap::new(),
+            local_vars: vec![],
+            function_defs: HashMap::new(),
+            special_vars: SpecialVars::default(),
+            field_splitter: FieldSplitter::default(),
+            record_separator: RecordSeparator::default(),
+            csv_mode: false,
+            output_streams: OutputStreams::new(),
+            input_sources: InputSources::new(),
+            pending_flow: None,
+            active_ranges: HashSet::new(),
+            regex_cache: RegexCache::new(),
+            random: Random::new(),
+            exit_code: 0,
+        }
+    }
 
-        self.interpret(&program_ast, records_reader);
+    /// Sets a variable before the program runs, such as FILENAME or a variable given on the
+    /// command line
+    pub fn set_variable(&mut self, name: &str, value: &str) {
+        let place = self.resolve_place(&LValue::variable(name));
+
+        self.assign(&place, Value::String(value.to_string()));
     }
 
-    fn interpret(&mut self, program_ast: &Program, records_reader: &mut dyn BufRead) {
+    /// Makes the interpreter read its input as CSV, and quote the fields that it prints as CSV
+    pub fn enable_csv_mode(&mut self) {
+        self.csv_mode = true;
+        self.update_separators();
+    }
+
+    /// Runs the program, returning its exit status
+    pub fn run(&mut self, program_str: &str, records_reader: Box<dyn BufRead>) -> i32 {
+        let program_ast = match parse(program_str) {
+            Ok(program_ast) => program_ast,
+            Err(err) => {
+                eprintln!("{}", err);
+
+                // This matches the exit status used by other awk implementations for syntax
+                // errors
+                return 2;
+            }
+        };
+
+        self.input_sources.set_main_input(records_reader);
+
+        self.interpret(&program_ast)
+    }
+
+    fn interpret(&mut self, program_ast: &Program) -> i32 {
         // Copy function definitions over to pseudo-global interpreter state
         self.function_defs.clone_from(&program_ast.function_defs);
 
+        let mut exited = false;
+
         // Execute BEGIN blocks
         for pattern_block in &program_ast.pattern_blocks {
             if let Some(crate::Pattern::Begin) = pattern_block.pattern {
                 if let Some(block) = &pattern_block.block {
-                    let return_value = self.execute_block(block);
-
-                    // TODO(Chris): Implement better error handling for return statements outside
-                    // of functions
-                    if return_value.is_some() {
-                        panic!("Used a return statement outside of a function");
+                    if self.execute_special_block(block, "BEGIN") {
+                        exited = true;
+                        break;
                     }
                 } else {
                     // This is required by the POSIX standard. Though we don't need to support the
@@ -50,108 +138,237 @@ impl Interpreter {
             }
         }
 
-        let mut curr_line_num = 0.0;
-
-        for line in records_reader.lines() {
+        // An exit statement skips the rest of the input, but END blocks are still executed
+        if !exited {
             // TODO(Chris): Handle cases where UTF-8 doesn't parse correctly
-            self.curr_line = line.unwrap();
-            let chars: Vec<char> = self.curr_line.chars().collect();
-
-            self.curr_columns.clear();
-
-            let mut prev_ch = '\0';
-            for ch in chars {
-                if (prev_ch.is_ascii_whitespace() || self.curr_columns.is_empty())
-                    && !ch.is_ascii_whitespace()
-                {
-                    self.curr_columns.push(String::new());
-                    // TODO(Chris): Refactor this (and memory allocation) into its own type, with its
-                    // own method
-                    let columns_len = self.curr_columns.len();
-                    self.curr_columns[columns_len - 1].push(ch);
-                } else if !ch.is_ascii_whitespace() {
-                    let columns_len = self.curr_columns.len();
-                    self.curr_columns[columns_len - 1].push(ch);
+            while let Some(record) = self.read_main_record().unwrap() {
+                self.special_vars.rt = record.terminator;
+                self.curr_line = record.text;
+                self.split_columns();
+
+                match self.eval_pattern_blocks(&program_ast.pattern_blocks) {
+                    ControlFlow::Nextfile => self.input_sources.skip_main_file(),
+                    ControlFlow::Exit => break,
+                    _ => (),
                 }
-
-                prev_ch = ch;
             }
-
-            curr_line_num += 1.0;
-            // TODO(Chris): Use the once_cell library to only create the NR string once
-            let nr_variable = self.lookup(&Id("NR".to_string()));
-            *nr_variable = Value::Num(curr_line_num);
-
-            self.eval_pattern_blocks(&program_ast.pattern_blocks);
         }
 
         // Execute END blocks
         for pattern_block in &program_ast.pattern_blocks {
             if let Some(crate::Pattern::End) = pattern_block.pattern {
                 if let Some(block) = &pattern_block.block {
-                    let return_value = self.execute_block(block);
-
-                    if return_value.is_some() {
-                        panic!("Used a return statement outside of a function");
+                    // An exit statement inside an END block stops the program immediately
+                    if self.execute_special_block(block, "END") {
+                        break;
                     }
                 } else {
                     panic!("END block must have an associated action.");
                 }
             }
         }
+
+        self.output_streams.close_all();
+        self.input_sources.close_all();
+
+        self.exit_code
+    }
+
+    /// Executes the action of a BEGIN or END block, returning whether it used an exit statement
+    fn execute_special_block(&mut self, block: &Block, block_name: &str) -> bool {
+        match self.execute_block(block) {
+            ControlFlow::Normal => false,
+            ControlFlow::Exit => true,
+            // TODO(Chris): Implement better error handling for return statements outside of
+            // functions
+            ControlFlow::Return(_) => panic!("Used a return statement outside of a function"),
+            ControlFlow::Next | ControlFlow::Nextfile => {
+                panic!("Used next or nextfile in a {} block", block_name)
+            }
+            ControlFlow::Break | ControlFlow::Continue => {
+                unreachable!("The parser only allows break and continue inside of loops")
+            }
+        }
+    }
+
+    /// Stops the program because of an error in a value it computed, such as a negative column
+    /// number. Output that was already written is flushed first, as when the program exits.
+    fn runtime_error(&mut self, message: &str) -> ! {
+        self.output_streams.close_all();
+        self.input_sources.close_all();
+
+        eprintln!("chawk: runtime error: {}", message);
+
+        // Like syntax errors, this matches the exit status used by other awk implementations
+        process::exit(2);
     }
 
-    fn eval_pattern_blocks(&mut self, pattern_blocks: &[PatternBlock]) {
-        for pattern_block in pattern_blocks {
+    /// Stops the program because a dynamic regex (or FS or RS) isn't a valid regex
+    fn invalid_regex(&mut self, pattern: &str, err: regex::Error) -> ! {
+        self.runtime_error(&format!("invalid regex /{}/: {}", pattern, err))
+    }
+
+    /// Reads the next record of the main input, updating NR and FNR
+    fn read_main_record(&mut self) -> io::Result<Option<Record>> {
+        let record = self
+            .input_sources
+            .read_main_record(&self.record_separator)?;
+
+        if record.is_some() {
+            self.special_vars.nr += 1.0;
+            self.special_vars.fnr += 1.0;
+        }
+
+        Ok(record)
+    }
+
+    /// Splits the current line into columns, using FS
+    fn split_columns(&mut self) {
+        self.curr_columns = self.field_splitter.split(&self.curr_line);
+    }
+
+    /// Returns how control flow left the pattern blocks for the current record
+    fn eval_pattern_blocks(&mut self, pattern_blocks: &[PatternBlock]) -> ControlFlow {
+        for (block_index, pattern_block) in pattern_blocks.iter().enumerate() {
             if let Some(pattern) = &pattern_block.pattern {
                 match pattern {
                     Pattern::Expression(expression) => {
                         let value = self.eval_exp(expression);
+
+                        if let Some(flow) = self.pending_flow.take() {
+                            return flow;
+                        }
+
                         if !value.to_bool() {
                             continue;
                         }
                     }
+                    Pattern::Range { start, end } => {
+                        let in_range = self.eval_range(block_index, start, end);
+
+                        if let Some(flow) = self.pending_flow.take() {
+                            return flow;
+                        }
+
+                        if !in_range {
+                            continue;
+                        }
+                    }
                     Pattern::Begin | Pattern::End => continue,
                 }
             }
 
             if let Some(block) = &pattern_block.block {
-                let return_value = self.execute_block(block);
-
-                if return_value.is_some() {
-                    panic!("Used a return statement outside of a function");
+                match self.execute_block(block) {
+                    ControlFlow::Normal => (),
+                    ControlFlow::Return(_) => {
+                        panic!("Used a return statement outside of a function")
+                    }
+                    // `next` skips the remaining pattern blocks for this record
+                    flow => return flow,
                 }
             } else {
-                println!("{}", self.curr_line);
+                let output = format!("{}{}", self.curr_line, self.special_vars.ors);
+
+                self.output_streams.write_stdout(&output);
             }
         }
+
+        ControlFlow::Normal
+    }
+
+    /// Returns whether the current record is part of a range pattern, updating whether the range
+    /// is active
+    fn eval_range(&mut self, block_index: usize, start: &Expression, end: &Expression) -> bool {
+        if !self.active_ranges.contains(&block_index) {
+            if !self.eval_exp(start).to_bool() {
+                return false;
+            }
+
+            self.active_ranges.insert(block_index);
+        }
+
+        // The record that starts a range can also end it
+        if self.eval_exp(end).to_bool() {
+            self.active_ranges.remove(&block_index);
+        }
+
+        true
     }
 
-    /// Returns an optional "return" value from within a function
-    fn execute_block(&mut self, block: &Block) -> Option<Value> {
+    /// Returns how control flow left the block
+    fn execute_block(&mut self, block: &Block) -> ControlFlow {
         self.local_vars.push(HashMap::new());
 
-        let mut return_value = None;
+        let mut flow = ControlFlow::Normal;
 
         for statement in &block.statements {
-            if let Some(stm_return_value) = self.execute_statement(statement) {
-                return_value = Some(stm_return_value);
+            flow = self.execute_statement(statement);
+
+            if !matches!(flow, ControlFlow::Normal) {
                 break;
             }
         }
 
         self.local_vars.pop();
 
-        return_value
+        flow
     }
 
-    /// Returns an optional "return" value from within a function
-    fn execute_statement(&mut self, statement: &Statement) -> Option<Value> {
+    /// Returns how control flow left the statement
+    fn execute_statement(&mut self, statement: &Statement) -> ControlFlow {
+        let flow = self.execute_statement_kind(statement);
+
+        // A function called by the statement may have used `next` or `exit`
+        self.pending_flow.take().unwrap_or(flow)
+    }
+
+    fn execute_statement_kind(&mut self, statement: &Statement) -> ControlFlow {
         match statement {
-            Statement::PrintStatement(PrintStatement { expression }) => {
-                let expression_value = self.eval_exp(expression);
+            Statement::PrintStatement(PrintStatement {
+                expressions,
+                redirection,
+            }) => {
+                let output = if expressions.is_empty() {
+                    self.curr_line.clone()
+                } else {
+                    // Numbers are printed using OFMT rather than CONVFMT
+                    let values: Vec<_> = expressions
+                        .iter()
+                        .map(|expr| {
+                            let value = self.eval_exp(expr);
+                            value.to_string_with_format(&self.special_vars.ofmt)
+                        })
+                        .collect();
+
+                    self.join_fields(&values)
+                };
 
-                println!("{}", expression_value);
+                // Don't print anything if a function in the statement used `next` or `exit`
+                if self.pending_flow.is_some() {
+                    return ControlFlow::Normal;
+                }
+
+                let output = output + &self.special_vars.ors;
+
+                self.write_output(redirection, &output);
+            }
+            Statement::PrintfStatement(PrintfStatement {
+                format,
+                arguments,
+                redirection,
+            }) => {
+                let format_value = self.eval_string(format);
+                let argument_values: Vec<_> =
+                    arguments.iter().map(|arg| self.eval_exp(arg)).collect();
+
+                if self.pending_flow.is_some() {
+                    return ControlFlow::Normal;
+                }
+
+                let output = sprintf(&format_value, &argument_values);
+
+                self.write_output(redirection, &output);
             }
             Statement::LocalVarStatement {
                 id,
@@ -181,11 +398,7 @@ impl Interpreter {
                 self.eval_exp(expression);
             }
             Statement::BlockStatement(other_block) => {
-                let return_value = self.execute_block(other_block);
-
-                if return_value.is_some() {
-                    return return_value;
-                }
+                return self.execute_block(other_block);
             }
             Statement::IfStatement {
                 condition,
@@ -194,22 +407,22 @@ impl Interpreter {
             } => {
                 let cond_value = self.eval_exp(condition);
 
-                let mut return_value = None;
+                let mut flow = ControlFlow::Normal;
 
                 self.local_vars.push(HashMap::new());
 
                 if cond_value.to_bool() {
-                    return_value = self.execute_statement(true_statement);
+                    flow = self.execute_statement(true_statement);
                 } else if let Some(false_statement) = false_statement {
-                    return_value = self.execute_statement(false_statement);
+                    flow = self.execute_statement(false_statement);
                 }
 
                 self.local_vars.pop();
 
-                return return_value;
+                return flow;
             }
             Statement::WhileStatement { condition, body } => {
-                let mut return_value;
+                let mut flow;
                 let mut cond_bool;
                 loop {
                     cond_bool = self.eval_exp(condition).to_bool();
@@ -220,15 +433,34 @@ impl Interpreter {
 
                     self.local_vars.push(HashMap::new());
 
-                    return_value = self.execute_statement(body);
+                    flow = self.execute_statement(body);
 
                     self.local_vars.pop();
 
-                    if return_value.is_some() {
-                        return return_value;
+                    match flow {
+                        ControlFlow::Normal | ControlFlow::Continue => (),
+                        ControlFlow::Break => break,
+                        _ => return flow,
                     }
                 }
             }
+            Statement::DoWhileStatement { body, condition } => loop {
+                self.local_vars.push(HashMap::new());
+
+                let flow = self.execute_statement(body);
+
+                self.local_vars.pop();
+
+                match flow {
+                    ControlFlow::Normal | ControlFlow::Continue => (),
+                    ControlFlow::Break => break,
+                    _ => return flow,
+                }
+
+                if !self.eval_exp(condition).to_bool() {
+                    break;
+                }
+            },
             Statement::ForStatement {
                 init_clause,
                 condition_expression,
@@ -238,7 +470,8 @@ impl Interpreter {
                 // NOTE(Chris): This is mostly based on the specification for a `for` loop provided
                 // at https://en.cppreference.com/w/c/language/for
 
-                let mut return_value;
+                // How control flow leaves the loop as a whole
+                let mut loop_flow = ControlFlow::Normal;
 
                 match init_clause {
                     Some(InitClause::Expression(expr)) => {
@@ -248,8 +481,8 @@ impl Interpreter {
                         self.local_vars.push(HashMap::new());
 
                         // NOTE(Chris): This should only be a local variable declaration statement,
-                        // so we should be able to ignore the return value (which represents an
-                        // awk-function's possible return value)
+                        // so we should be able to ignore its control flow (which could only
+                        // represent an awk-function's return value)
                         _ = self.execute_statement(decl_statement);
                     }
                     None => (),
@@ -270,12 +503,18 @@ impl Interpreter {
 
                     self.local_vars.push(HashMap::new());
 
-                    return_value = self.execute_statement(body);
+                    let flow = self.execute_statement(body);
 
                     self.local_vars.pop();
 
-                    if return_value.is_some() {
-                        return return_value;
+                    // The iteration expression still runs after a `continue`
+                    match flow {
+                        ControlFlow::Normal | ControlFlow::Continue => (),
+                        ControlFlow::Break => break,
+                        _ => {
+                            loop_flow = flow;
+                            break;
+                        }
                     }
 
                     if let Some(iteration_expression) = iteration_expression {
@@ -286,34 +525,90 @@ impl Interpreter {
                 if let Some(InitClause::Declaration(_decl_statement)) = init_clause {
                     self.local_vars.pop();
                 }
+
+                return loop_flow;
+            }
+            Statement::ForInStatement { key, array, body } => {
+                // NOTE(Chris): We iterate over a sorted copy of the keys, both so that the body
+                // can freely modify the array and so that the iteration order is deterministic
+                let mut keys: Vec<String> =
+                    self.lookup_array(array).borrow().keys().cloned().collect();
+                keys.sort();
+
+                let key_place = self.resolve_place(&LValue::variable(&key.0));
+
+                for key_string in keys {
+                    self.assign(&key_place, Value::String(key_string));
+
+                    self.local_vars.push(HashMap::new());
+
+                    let flow = self.execute_statement(body);
+
+                    self.local_vars.pop();
+
+                    match flow {
+                        ControlFlow::Normal | ControlFlow::Continue => (),
+                        ControlFlow::Break => break,
+                        _ => return flow,
+                    }
+                }
             }
             Statement::ReturnStatement(expression) => {
                 let value = self.eval_exp(expression);
 
-                return Some(value);
+                return ControlFlow::Return(value);
+            }
+            Statement::DeleteStatement { array, subscripts } => {
+                let key = subscripts
+                    .as_ref()
+                    .map(|subscripts| self.subscript_key(subscripts));
+
+                let array = self.lookup_array(array);
+
+                if let Some(key) = key {
+                    array.borrow_mut().remove(&key);
+                } else {
+                    array.borrow_mut().clear();
+                }
+            }
+            Statement::BreakStatement => return ControlFlow::Break,
+            Statement::ContinueStatement => return ControlFlow::Continue,
+            Statement::NextStatement => return ControlFlow::Next,
+            Statement::NextfileStatement => return ControlFlow::Nextfile,
+            Statement::ExitStatement(status) => {
+                // Without a status, we keep the status from any previous exit statement
+                if let Some(status) = status {
+                    self.exit_code = self.eval_exp(status).to_num() as i32;
+                }
+
+                return ControlFlow::Exit;
             }
         }
 
-        None
+        ControlFlow::Normal
     }
 
     fn eval_exp(&mut self, expression: &Expression) -> Value {
+        // Once a function has used `next` or `exit`, the rest of the statement is skipped
+        if self.pending_flow.is_some() {
+            return Value::String(String::new());
+        }
+
         match expression {
             Expression::String { value } => Value::String(value.clone()),
-            Expression::ColumnNumber(num) => {
-                Value::String(if self.curr_columns.len() >= *num as usize {
-                    if *num == 0 {
-                        self.curr_line.clone()
-                    } else {
-                        let col_index = num - 1;
+            Expression::ColumnNumber(column_expression) => {
+                let place = self.resolve_column(column_expression);
 
-                        self.curr_columns[col_index as usize].to_string()
-                    }
-                } else {
-                    "".to_string()
-                })
+                self.fetch(&place)
             }
             Expression::VarLookup(var_id) => self.lookup(var_id).clone(),
+            Expression::SpecialVarLookup(SpecialVar::Nf) => self.fetch(&Place::NumFields),
+            Expression::SpecialVarLookup(special_var) => self.special_vars.get(*special_var),
+            Expression::ArrayLookup { id, subscripts } => {
+                let place = self.resolve_array_element(id, subscripts);
+
+                self.fetch(&place)
+            }
             Expression::Plus(expr_left, expr_right) => {
                 self.apply_arith(expr_left, Add::add, expr_right)
             }
@@ -329,22 +624,16 @@ impl Interpreter {
             Expression::Modulo(expr_left, expr_right) => {
                 self.apply_arith(expr_left, Rem::rem, expr_right)
             }
+            Expression::Pow(expr_left, expr_right) => {
+                self.apply_arith(expr_left, f64::powf, expr_right)
+            }
+            Expression::LogicalNot(expr) => Value::from_bool(!self.eval_exp(expr).to_bool()),
+            Expression::UnaryMinus(expr) => Value::Num(-self.eval_exp(expr).to_num()),
+            Expression::UnaryPlus(expr) => Value::Num(self.eval_exp(expr).to_num()),
             Expression::Num(num) => Value::Num(*num),
             Expression::Concatenate(expr_left, expr_right) => {
-                let value_left = self.eval_exp(expr_left);
-                let value_right = self.eval_exp(expr_right);
-
-                let string_result = match (&value_left, &value_right) {
-                    (Value::String(string_left), Value::String(string_right)) => {
-                        let mut string_result = String::new();
-                        string_result.push_str(string_left);
-                        string_result.push_str(string_right);
-                        string_result
-                    }
-                    _ => {
-                        format!("{}{}", &value_left, &value_right)
-                    }
-                };
+                let mut string_result = self.eval_string(expr_left);
+                string_result.push_str(&self.eval_string(expr_right));
 
                 Value::String(string_result)
             }
@@ -372,35 +661,56 @@ impl Interpreter {
             Expression::LogicalOr(expr_left, expr_right) => Value::from_bool(
                 self.eval_exp(expr_left).to_bool() || self.eval_exp(expr_right).to_bool(),
             ),
+            Expression::Conditional {
+                condition,
+                true_expression,
+                false_expression,
+            } => {
+                // Only the selected branch is evaluated
+                if self.eval_exp(condition).to_bool() {
+                    self.eval_exp(true_expression)
+                } else {
+                    self.eval_exp(false_expression)
+                }
+            }
             Expression::Regex(regex) => {
                 // According to the POSIX standard, we treat the regex expression /ere/ as the
                 // equivalent of $0 ~ /ere/, unless it's the right-hand of `~`, `!~`, or used as an
                 // argument to the built-in gsub, match, and sub functions.
                 // If we're directly evaluating a regex as an expression, then we're not using it
-                // with `~` or  `!~`.
+                // with `~` or `!~`, and the built-in functions handle regex arguments themselves.
                 Value::from_bool(regex.is_match(&self.curr_line))
             }
-            Expression::Assign(id, rhs_expression) => {
+            Expression::Assign(lvalue, rhs_expression) => {
+                let place = self.resolve_place(lvalue);
                 let expression_value = self.eval_exp(rhs_expression);
 
-                let var_value = self.lookup(id);
-
-                *var_value = expression_value.clone();
+                self.assign(&place, expression_value.clone());
 
                 expression_value
             }
-            Expression::PlusAssign(id, rhs_expression) => {
-                let var_value_num = self.lookup(id).to_num();
-                let expression_value_num = self.eval_exp(rhs_expression).to_num();
-
-                // NOTE(Chris): We call lookup() a second time to avoid mutably borrowing self
-                // twice
-                let var_value = self.lookup(id);
-
-                *var_value = Value::Num(var_value_num + expression_value_num);
-
-                var_value.clone()
+            Expression::PlusAssign(lvalue, rhs_expression) => {
+                self.apply_arith_assign(lvalue, Add::add, rhs_expression)
+            }
+            Expression::MinusAssign(lvalue, rhs_expression) => {
+                self.apply_arith_assign(lvalue, Sub::sub, rhs_expression)
+            }
+            Expression::TimesAssign(lvalue, rhs_expression) => {
+                self.apply_arith_assign(lvalue, Mul::mul, rhs_expression)
+            }
+            Expression::DivAssign(lvalue, rhs_expression) => {
+                self.apply_arith_assign(lvalue, Div::div, rhs_expression)
+            }
+            Expression::ModuloAssign(lvalue, rhs_expression) => {
+                self.apply_arith_assign(lvalue, Rem::rem, rhs_expression)
             }
+            Expression::PowAssign(lvalue, rhs_expression) => {
+                self.apply_arith_assign(lvalue, f64::powf, rhs_expression)
+            }
+            Expression::PreIncrement(lvalue) => self.apply_increment(lvalue, 1.0).1,
+            Expression::PreDecrement(lvalue) => self.apply_increment(lvalue, -1.0).1,
+            Expression::PostIncrement(lvalue) => self.apply_increment(lvalue, 1.0).0,
+            Expression::PostDecrement(lvalue) => self.apply_increment(lvalue, -1.0).0,
             Expression::RegexMatch(expr_left, expr_right) => {
                 Value::from_bool(self.apply_regex_from_right(expr_left, expr_right))
             }
@@ -408,6 +718,11 @@ impl Interpreter {
                 Value::from_bool(!self.apply_regex_from_right(expr_left, expr_right))
             }
             Expression::FunctionCall { name, arguments } => {
+                // Built-in functions take precedence over user-defined functions
+                if let Some(builtin) = lookup_builtin(&name.0) {
+                    return builtin(self, arguments);
+                }
+
                 let function_def = if let Some(function_def) = self.function_defs.get(name) {
                     // TODO(Chris): Initialize function definitions with once_cell to avoid cloning
                     // here
@@ -418,6 +733,10 @@ impl Interpreter {
                 };
 
                 let mut new_context = HashMap::new();
+                // Untyped variables which are passed by name, along with their parameters. If the
+                // function uses one of these parameters as an array, the caller's variable becomes
+                // that array too.
+                let mut untyped_args = vec![];
 
                 for (i, arg) in arguments.iter().enumerate() {
                     let param_name = if let Some(param_name) = function_def.parameters.get(i) {
@@ -432,6 +751,12 @@ impl Interpreter {
                         );
                     };
 
+                    if let Expression::VarLookup(id) = arg.as_ref() {
+                        if self.is_untyped(id) {
+                            untyped_args.push((id, param_name));
+                        }
+                    }
+
                     let value = self.eval_exp(arg);
 
                     new_context.insert(param_name.clone(), value);
@@ -441,17 +766,93 @@ impl Interpreter {
 
                 swap(&mut function_vars, &mut self.local_vars);
 
-                let return_value = self.execute_block(&function_def.body);
+                let flow = self.execute_block(&function_def.body);
 
                 swap(&mut function_vars, &mut self.local_vars);
 
-                // TODO(Chris): Return the empty string rather than FALSE_VALUE once you have a
-                // universal empty string value
-                return_value.unwrap_or(FALSE_VALUE)
+                for (id, param_name) in untyped_args {
+                    if let Some(Value::Array(array)) = function_vars[0].get(param_name) {
+                        let value = self.lookup(id);
+
+                        if matches!(value, Value::String(string) if string.is_empty()) {
+                            *value = Value::Array(array.clone());
+                        }
+                    }
+                }
+
+                match flow {
+                    ControlFlow::Return(value) => value,
+                    // TODO(Chris): Return the empty string rather than FALSE_VALUE once you have a
+                    // universal empty string value
+                    ControlFlow::Normal => FALSE_VALUE,
+                    // These take effect once the statement calling the function is done
+                    flow => {
+                        self.pending_flow = Some(flow);
+                        FALSE_VALUE
+                    }
+                }
+            }
+            Expression::Getline { source, lvalue } => {
+                let record = match source {
+                    GetlineSource::MainInput => self.read_main_record(),
+                    GetlineSource::File(file_expr) => {
+                        let file_name = self.eval_string(file_expr);
+
+                        self.input_sources
+                            .read_file_record(&file_name, &self.record_separator)
+                    }
+                    GetlineSource::Command(command_expr) => {
+                        let command = self.eval_string(command_expr);
+                        let record = self
+                            .input_sources
+                            .read_command_record(&command, &self.record_separator);
+
+                        if let Ok(Some(_)) = record {
+                            self.special_vars.nr += 1.0;
+                        }
+
+                        record
+                    }
+                };
+
+                match record {
+                    Ok(Some(record)) => {
+                        let place = match lvalue {
+                            Some(lvalue) => self.resolve_place(lvalue),
+                            None => Place::Column(0),
+                        };
+
+                        self.special_vars.rt = record.terminator;
+                        self.assign(&place, Value::String(record.text));
+
+                        Value::Num(1.0)
+                    }
+                    Ok(None) => Value::Num(0.0),
+                    Err(_) => Value::Num(-1.0),
+                }
+            }
+            Expression::In { subscripts, array } => {
+                let key = self.subscript_key(subscripts);
+
+                Value::from_bool(self.lookup_array(array).borrow().contains_key(&key))
             }
         }
     }
 
+    /// Evaluates an expression and converts it to a string, using CONVFMT for numbers
+    fn eval_string(&mut self, expression: &Expression) -> String {
+        let value = self.eval_exp(expression);
+
+        self.convert_to_string(value)
+    }
+
+    fn convert_to_string(&self, value: Value) -> String {
+        match value {
+            Value::String(string) => string,
+            value => value.to_string_with_format(&self.special_vars.convfmt),
+        }
+    }
+
     fn apply_arith(
         &mut self,
         expr_left: &Expression,
@@ -464,6 +865,35 @@ impl Interpreter {
         ))
     }
 
+    fn apply_arith_assign(
+        &mut self,
+        lvalue: &LValue,
+        f: impl Fn(f64, f64) -> f64,
+        rhs_expression: &Expression,
+    ) -> Value {
+        let place = self.resolve_place(lvalue);
+        let var_value_num = self.fetch(&place).to_num();
+        let expression_value_num = self.eval_exp(rhs_expression).to_num();
+
+        let new_value = Value::Num(f(var_value_num, expression_value_num));
+
+        self.assign(&place, new_value.clone());
+
+        new_value
+    }
+
+    /// Adds `amount` to an lvalue, returning its numeric values from before and after
+    fn apply_increment(&mut self, lvalue: &LValue, amount: f64) -> (Value, Value) {
+        let place = self.resolve_place(lvalue);
+        let old_num = self.fetch(&place).to_num();
+
+        let new_value = Value::Num(old_num + amount);
+
+        self.assign(&place, new_value.clone());
+
+        (Value::Num(old_num), new_value)
+    }
+
     fn apply_cmp(
         &mut self,
         expr_left: &Expression,
@@ -474,12 +904,15 @@ impl Interpreter {
         let value_left = self.eval_exp(expr_left);
         let value_right = self.eval_exp(expr_right);
 
-        Value::from_bool(match (&value_left, &value_right) {
+        Value::from_bool(match (value_left, value_right) {
             (Value::String(string_left), Value::String(string_right)) => {
-                cmp_string(string_left, string_right)
+                cmp_string(&string_left, &string_right)
             }
-            (Value::Num(num_left), Value::Num(num_right)) => cmp_float(num_left, num_right),
-            _ => cmp_string(&value_left.to_string(), &value_right.to_string()),
+            (Value::Num(num_left), Value::Num(num_right)) => cmp_float(&num_left, &num_right),
+            (value_left, value_right) => cmp_string(
+                &self.convert_to_string(value_left),
+                &self.convert_to_string(value_right),
+            ),
         })
     }
 
@@ -489,16 +922,19 @@ impl Interpreter {
             return false;
         }
 
-        let value_left = self.eval_exp(expr_left);
+        let string_left = self.eval_string(expr_left);
 
         if let Expression::Regex(regex) = expr_right {
-            regex.is_match(&value_left.to_string())
+            regex.is_match(&string_left)
         } else {
-            // TODO(Chris): Treat this as a full regex by converting the corresponding
-            // string value into a regex in the parser
-
-            let value_right = self.eval_exp(expr_right);
-            value_left.to_string().contains(&value_right.to_string())
+            // Any other value is used as a dynamic regex
+            let pattern = self.eval_string(expr_right);
+            let regex = self
+                .regex_cache
+                .get(&pattern)
+                .unwrap_or_else(|err| self.invalid_regex(&pattern, err));
+
+            regex.is_match(&string_left)
         }
     }
 
@@ -521,12 +957,248 @@ impl Interpreter {
             self.global_vars.get_mut(id).unwrap()
         }
     }
+
+    // NOTE(Chris): Like scalars, arrays are created on first use. A variable that has only ever
+    // been read as a scalar is still the empty string, so it can become an array too.
+    /// Returns whether a variable has never been given a value, so it could still become an array
+    fn is_untyped(&self, id: &Id) -> bool {
+        let value = match self
+            .local_vars
+            .iter()
+            .rev()
+            .find_map(|context| context.get(id))
+        {
+            Some(value) => Some(value),
+            None => self.global_vars.get(id),
+        };
+
+        match value {
+            Some(Value::String(string)) => string.is_empty(),
+            Some(_) => false,
+            None => true,
+        }
+    }
+
+    fn lookup_array(&mut self, id: &Id) -> Array {
+        let value = self.lookup(id);
+
+        match value {
+            Value::Array(array) => array.clone(),
+            Value::String(string) if string.is_empty() => {
+                let array = Array::default();
+                *value = Value::Array(array.clone());
+                array
+            }
+            _ => panic!("Tried to use scalar variable as an array: {}", id),
+        }
+    }
+
+    /// Evaluates the subscript of an lvalue (if any), so that it's only evaluated once when we
+    /// both read from and write to the lvalue
+    fn resolve_place(&mut self, lvalue: &LValue) -> Place {
+        match lvalue {
+            LValue::Variable(id) => Place::Variable(id.clone()),
+            LValue::Special(SpecialVar::Nf) => Place::NumFields,
+            LValue::Special(special_var) => Place::Special(*special_var),
+            LValue::ArrayElement { id, subscripts } => self.resolve_array_element(id, subscripts),
+            LValue::Column(column_expression) => self.resolve_column(column_expression),
+        }
+    }
+
+    fn resolve_column(&mut self, column_expression: &Expression) -> Place {
+        // Column numbers are truncated towards zero, so $1.9 is the same as $1
+        let column_num = self.eval_exp(column_expression).to_num().trunc();
+
+        if column_num < 0.0 {
+            self.runtime_error(&format!("negative field index ${}", column_num));
+        }
+
+        Place::Column(column_num as usize)
+    }
+
+    fn resolve_array_element(&mut self, id: &Id, subscripts: &[Expression]) -> Place {
+        let key = self.subscript_key(subscripts);
+
+        Place::ArrayElement(self.lookup_array(id), key)
+    }
+
+    /// Builds an array key, joining multiple subscripts (as in `arr[i, j]`) with SUBSEP
+    fn subscript_key(&mut self, subscripts: &[Expression]) -> String {
+        if let [subscript] = subscripts {
+            return self.eval_string(subscript);
+        }
+
+        let keys: Vec<String> = subscripts
+            .iter()
+            .map(|subscript| self.eval_string(subscript))
+            .collect();
+
+        keys.join(&self.special_vars.subsep)
+    }
+
+    fn fetch(&mut self, place: &Place) -> Value {
+        match place {
+            Place::Variable(id) => self.lookup(id).clone(),
+            // Referencing an array element that doesn't exist creates it
+            Place::ArrayElement(array, key) => array
+                .borrow_mut()
+                .entry(key.clone())
+                .or_insert_with(|| Value::String(String::new()))
+                .clone(),
+            Place::Special(special_var) => self.special_vars.get(*special_var),
+            Place::NumFields => Value::Num(self.curr_columns.len() as f64),
+            Place::Column(0) => Value::String(self.curr_line.clone()),
+            Place::Column(column_num) => Value::String(
+                self.curr_columns
+                    .get(column_num - 1)
+                    .cloned()
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+
+    fn assign(&mut self, place: &Place, value: Value) {
+        // Once a function has used `next` or `exit`, the rest of the statement has no effect
+        if self.pending_flow.is_some() {
+            return;
+        }
+
+        if let Value::Array(_) = value {
+            panic!("Tried to assign an array to a scalar");
+        }
+
+        match place {
+            Place::Variable(id) => {
+                let var_value = self.lookup(id);
+
+                if let Value::Array(_) = var_value {
+                    panic!("Tried to assign a scalar to an array: {}", id);
+                }
+
+                *var_value = value;
+            }
+            Place::ArrayElement(array, key) => {
+                array.borrow_mut().insert(key.clone(), value);
+            }
+            Place::Special(special_var) => {
+                self.special_vars.set(*special_var, &value);
+
+                // The new separators take effect from the next record
+                if let SpecialVar::Fs | SpecialVar::Rs = special_var {
+                    self.update_separators();
+                }
+            }
+            Place::Column(0) => {
+                self.curr_line = self.convert_to_string(value);
+                self.split_columns();
+            }
+            Place::Column(column_num) => {
+                // Assigning past the last column creates empty columns in between
+                if *column_num > self.curr_columns.len() {
+                    self.curr_columns.resize(*column_num, String::new());
+                }
+
+                self.curr_columns[column_num - 1] = self.convert_to_string(value);
+                self.rebuild_line();
+            }
+            Place::NumFields => {
+                let num_fields = value.to_num();
+
+                if num_fields < 0.0 {
+                    self.runtime_error(&format!("negative value assigned to NF: {}", num_fields));
+                }
+
+                // This either truncates the record or extends it with empty fields
+                self.curr_columns.resize(num_fields as usize, String::new());
+                self.rebuild_line();
+            }
+        }
+    }
+
+    /// Joins output fields with OFS, or as CSV in CSV mode
+    fn join_fields(&self, fields: &[String]) -> String {
+        if self.csv_mode {
+            let quoted_fields: Vec<_> = fields.iter().map(|field| quote_csv_field(field)).collect();
+
+            quoted_fields.join(",")
+        } else {
+            fields.join(&self.special_vars.ofs)
+        }
+    }
+
+    /// Rebuilds the field splitter and record separator after FS or RS has changed
+    fn update_separators(&mut self) {
+        if self.csv_mode {
+            self.record_separator = RecordSeparator::Csv;
+            self.field_splitter = FieldSplitter::Csv;
+
+            return;
+        }
+
+        match RecordSeparator::new(&self.special_vars.rs, &mut self.regex_cache) {
+            Ok(record_separator) => self.record_separator = record_separator,
+            Err(err) => self.invalid_regex(&self.special_vars.rs.clone(), err),
+        }
+
+        match FieldSplitter::new(&self.special_vars.fs, &mut self.regex_cache) {
+            Ok(field_splitter) => self.field_splitter = field_splitter,
+            Err(err) => self.invalid_regex(&self.special_vars.fs.clone(), err),
+        }
+
+        // In paragraph mode, newlines always separate fields, whatever FS is
+        if let RecordSeparator::Paragraph = self.record_separator {
+            self.field_splitter = self.field_splitter.clone().also_splitting_on_newlines();
+        }
+    }
+
+    /// Writes the output of a print or printf statement to stdout or its redirection
+    fn write_output(&mut self, redirection: &Option<OutputRedirection>, output: &str) {
+        match redirection {
+            Some(OutputRedirection { kind, target }) => {
+                let target_value = self.eval_string(target);
+
+                self.output_streams.write(*kind, &target_value, output);
+            }
+            None => self.output_streams.write_stdout(output),
+        }
+    }
+
+    /// Rebuilds the current line from its columns, after one of them has been modified
+    fn rebuild_line(&mut self) {
+        self.curr_line = self.join_fields(&self.curr_columns);
+    }
+}
+
+/// How control leaves a statement
+enum ControlFlow {
+    /// Continue on to the next statement
+    Normal,
+    Return(Value),
+    Next,
+    Nextfile,
+    Exit,
+    Break,
+    Continue,
+}
+
+/// The storage location referred to by an lvalue
+enum Place {
+    Variable(Id),
+    ArrayElement(Array, String),
+    Column(usize),
+    Special(SpecialVar),
+    // The special NF variable, which is computed from the current columns
+    NumFields,
 }
 
+// Arrays are passed to functions by reference, so they're shared between their owners
+pub type Array = Rc<RefCell<HashMap<String, Value>>>;
+
 #[derive(Debug, Clone)]
 pub enum Value {
     String(String),
     Num(f64),
+    Array(Array),
 }
 
 const TRUE_VALUE: Value = Value::Num(1.0);
@@ -535,30 +1207,35 @@ const FALSE_VALUE: Value = Value::Num(0.0);
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Value::String(string) => write!(f, "{}", string),
-            // The default float format for awk is [-]D.DDDDDD, according to
-            // https://en.wikibooks.org/wiki/An_Awk_Primer/Output_with_print_and_printf
-            // Also, according to the POSIX standard, OFMT is "%.6g" by default
-            Value::Num(num) => {
-                // NOTE(Chris): This code fails to avoid trailing zeros
-                // write!(f, "{:.6}", num)
-
-                // Modified from
-                // https://stackoverflow.com/questions/59506403/how-to-format-a-float-without-trailing-zeros-in-rust
-                const DECIMAL_FORMAT: f64 = 10_000.0;
-
-                let rounded = (num * DECIMAL_FORMAT).round() / DECIMAL_FORMAT;
-                write!(f, "{}", rounded)
+            // Without access to CONVFMT or OFMT, we use their default format
+            Value::String(_) | Value::Num(_) => {
+                write!(f, "{}", self.to_string_with_format(DEFAULT_NUMBER_FORMAT))
             }
+            Value::Array(_) => panic!("Tried to use an array in a scalar context"),
         }
     }
 }
 
 impl Value {
-    fn to_num(&self) -> f64 {
+    /// Converts the value to a string, formatting numbers with the given printf-style format
+    /// (such as CONVFMT or OFMT). As POSIX specifies, integers are converted as integers instead.
+    pub(crate) fn to_string_with_format(&self, format: &str) -> String {
+        match self {
+            Value::String(string) => string.clone(),
+            Value::Num(num) if num.fract() == 0.0 && num.abs() < i64::MAX as f64 => {
+                // This also turns -0 into 0
+                (*num as i64).to_string()
+            }
+            Value::Num(_) => sprintf(format, std::slice::from_ref(self)),
+            Value::Array(_) => panic!("Tried to use an array in a scalar context"),
+        }
+    }
+
+    pub(crate) fn to_num(&self) -> f64 {
         match self {
-            Value::String(string) => string.parse::<f64>().unwrap_or(0.0),
+            Value::String(string) => str_to_num(string),
             Value::Num(num) => *num,
+            Value::Array(_) => panic!("Tried to use an array in a scalar context"),
         }
     }
 
@@ -566,6 +1243,7 @@ impl Value {
         match self {
             Value::String(string) => !string.is_empty(),
             Value::Num(num) => num != &0.0,
+            Value::Array(_) => panic!("Tried to use an array in a scalar context"),
         }
     }
 
@@ -577,3 +1255,52 @@ impl Value {
         }
     }
 }
+
+/// Converts a string to a number like awk does, using its longest numeric prefix (ignoring leading
+/// whitespace). So "3.5kg" is 3.5, while "kg" is 0.
+fn str_to_num(string: &str) -> f64 {
+    let trimmed = string.trim_start();
+    let bytes = trimmed.as_bytes();
+
+    let count_digits = |start: usize| {
+        bytes[start..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count()
+    };
+
+    let mut end = 0;
+
+    if let Some(b'+' | b'-') = bytes.first() {
+        end += 1;
+    }
+
+    let integer_digits = count_digits(end);
+    end += integer_digits;
+
+    let mut fraction_digits = 0;
+    if bytes.get(end) == Some(&b'.') {
+        fraction_digits = count_digits(end + 1);
+        end += 1 + fraction_digits;
+    }
+
+    if integer_digits == 0 && fraction_digits == 0 {
+        return 0.0;
+    }
+
+    // Only treat an "e" as an exponent if digits actually follow it
+    if let Some(b'e' | b'E') = bytes.get(end) {
+        let mut exponent_end = end + 1;
+
+        if let Some(b'+' | b'-') = bytes.get(exponent_end) {
+            exponent_end += 1;
+        }
+
+        let exponent_digits = count_digits(exponent_end);
+        if exponent_digits > 0 {
+            end = exponent_end + exponent_digits;
+        }
+    }
+
+    trimmed[..end].parse().unwrap_or(0.0)
+}
@@ -0,0 +1,409 @@
+// Implements awk's built-in functions, which are looked up before user-defined functions.
+
+use std::rc::Rc;
+
+use regex::Regex;
+
+use super::{Interpreter, Place, Value};
+use crate::{fields::FieldSplitter, format::sprintf, Expression, Id, LValue};
+
+/// A built-in function, which receives its unevaluated arguments so that it can treat them as
+/// lvalues, arrays or regexes when needed
+pub(super) type Builtin = fn(&mut Interpreter, &[Box<Expression>]) -> Value;
+
+/// Returns the built-in function with the given name, if there is one
+pub(super) fn lookup_builtin(name: &str) -> Option<Builtin> {
+    let builtin: Builtin = match name {
+        "length" => length,
+        "substr" => substr,
+        "index" => index,
+        "split" => split,
+        "tolower" => tolower,
+        "toupper" => toupper,
+        "sub" => sub,
+        "gsub" => gsub,
+        "match" => builtin_match,
+        "sprintf" => builtin_sprintf,
+        "close" => close,
+        "fflush" => fflush,
+        "int" => int,
+        "sqrt" => sqrt,
+        "exp" => exp,
+        "log" => log,
+        "sin" => sin,
+        "cos" => cos,
+        "atan2" => atan2,
+        "rand" => rand,
+        "srand" => srand,
+        _ => return None,
+    };
+
+    Some(builtin)
+}
+
+/// Panics unless the number of arguments is within the given bounds
+fn check_arg_count(name: &str, arguments: &[Box<Expression>], min: usize, max: usize) {
+    if arguments.len() < min || arguments.len() > max {
+        if min == max {
+            panic!(
+                "{} requires {} arguments, but {} were used",
+                name,
+                min,
+                arguments.len()
+            );
+        } else {
+            panic!(
+                "{} requires between {} and {} arguments, but {} were used",
+                name,
+                min,
+                max,
+                arguments.len()
+            );
+        }
+    }
+}
+
+fn length(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    check_arg_count("length", arguments, 0, 1);
+
+    let value = match arguments.first() {
+        // Without an argument, this is the length of the current record
+        None => Value::String(interpreter.curr_line.clone()),
+        Some(argument) => interpreter.eval_exp(argument),
+    };
+
+    match value {
+        Value::Array(array) => Value::Num(array.borrow().len() as f64),
+        value => Value::Num(interpreter.convert_to_string(value).chars().count() as f64),
+    }
+}
+
+fn substr(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    check_arg_count("substr", arguments, 2, 3);
+
+    let string = interpreter.eval_string(&arguments[0]);
+    let chars: Vec<char> = string.chars().collect();
+
+    // Positions start at 1, and are rounded to the nearest integer
+    let start = interpreter.eval_exp(&arguments[1]).to_num().round();
+    let end = match arguments.get(2) {
+        Some(length) => start + interpreter.eval_exp(length).to_num().round(),
+        None => f64::INFINITY,
+    };
+
+    if start.is_nan() || end.is_nan() {
+        return Value::String(String::new());
+    }
+
+    // Only the part of the range which overlaps with the string is used
+    let first = start.max(1.0);
+    let last = end.min(chars.len() as f64 + 1.0);
+
+    if first >= last {
+        return Value::String(String::new());
+    }
+
+    Value::String(
+        chars[first as usize - 1..last as usize - 1]
+            .iter()
+            .collect(),
+    )
+}
+
+fn index(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    check_arg_count("index", arguments, 2, 2);
+
+    let string = interpreter.eval_string(&arguments[0]);
+    let target = interpreter.eval_string(&arguments[1]);
+
+    // Positions are counted in characters rather than bytes, starting at 1
+    let position = match string.find(&target) {
+        Some(byte_index) if !target.is_empty() => string[..byte_index].chars().count() + 1,
+        _ => 0,
+    };
+
+    Value::Num(position as f64)
+}
+
+fn split(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    check_arg_count("split", arguments, 2, 3);
+
+    let string = interpreter.eval_string(&arguments[0]);
+
+    let array = match arguments[1].as_ref() {
+        Expression::VarLookup(id) => interpreter.lookup_array(id),
+        _ => interpreter.runtime_error("the second argument to split must be an array"),
+    };
+
+    // The separator is used the same way as FS, which is also the default
+    let pieces: Vec<String> = match arguments.get(2).map(|separator| separator.as_ref()) {
+        Some(Expression::Regex(regex)) => FieldSplitter::Regex(Rc::clone(regex)).split(&string),
+        Some(separator) => {
+            let separator = interpreter.eval_string(separator);
+            FieldSplitter::new(&separator, &mut interpreter.regex_cache)
+                .unwrap_or_else(|err| interpreter.invalid_regex(&separator, err))
+                .split(&string)
+        }
+        None => interpreter.field_splitter.split(&string),
+    };
+
+    let mut array = array.borrow_mut();
+    array.clear();
+
+    for (i, piece) in pieces.iter().enumerate() {
+        array.insert((i + 1).to_string(), Value::String(piece.clone()));
+    }
+
+    Value::Num(pieces.len() as f64)
+}
+
+/// Evaluates an argument that's used as a regex. Regex literals are used as-is rather than being
+/// matched against the current record, while any other value is used as a dynamic regex.
+fn eval_regex(interpreter: &mut Interpreter, argument: &Expression) -> Rc<Regex> {
+    match argument {
+        Expression::Regex(regex) => Rc::clone(regex),
+        _ => {
+            let pattern = interpreter.eval_string(argument);
+            interpreter
+                .regex_cache
+                .get(&pattern)
+                .unwrap_or_else(|err| interpreter.invalid_regex(&pattern, err))
+        }
+    }
+}
+
+fn sub(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    apply_substitution("sub", interpreter, arguments, false)
+}
+
+fn gsub(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    apply_substitution("gsub", interpreter, arguments, true)
+}
+
+/// Replaces the first match (or every match, if `global` is true) of a regex in the target
+/// lvalue, returning the number of replacements made
+fn apply_substitution(
+    name: &str,
+    interpreter: &mut Interpreter,
+    arguments: &[Box<Expression>],
+    global: bool,
+) -> Value {
+    check_arg_count(name, arguments, 2, 3);
+
+    let regex = eval_regex(interpreter, &arguments[0]);
+    let replacement = interpreter.eval_string(&arguments[1]);
+
+    // The target defaults to the current record. Values that can't be assigned to are still
+    // searched, but the result is discarded.
+    let place = match arguments.get(2) {
+        Some(target) => as_lvalue(target).map(|lvalue| interpreter.resolve_place(&lvalue)),
+        None => Some(Place::Column(0)),
+    };
+
+    let target = match (&place, arguments.get(2)) {
+        (Some(place), _) => interpreter.fetch(place),
+        (None, Some(target)) => interpreter.eval_exp(target),
+        (None, None) => unreachable!("The target defaults to $0"),
+    };
+    let target = interpreter.convert_to_string(target);
+
+    let mut result = String::new();
+    let mut last_end = 0;
+    let mut num_replacements = 0;
+
+    for found in regex.find_iter(&target) {
+        result.push_str(&target[last_end..found.start()]);
+        push_replacement(&mut result, &replacement, found.as_str());
+
+        last_end = found.end();
+        num_replacements += 1;
+
+        if !global {
+            break;
+        }
+    }
+
+    if num_replacements > 0 {
+        result.push_str(&target[last_end..]);
+
+        if let Some(place) = place {
+            interpreter.assign(&place, Value::String(result));
+        }
+    }
+
+    Value::Num(num_replacements as f64)
+}
+
+/// Appends the replacement text for a single match, where `&` stands for the matched text, `\&`
+/// is a literal ampersand and `\\` is a literal backslash
+fn push_replacement(result: &mut String, replacement: &str, matched: &str) {
+    let mut chars = replacement.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '&' => result.push_str(matched),
+            '\\' => match chars.peek() {
+                Some(&escaped @ ('&' | '\\')) => {
+                    result.push(escaped);
+                    chars.next();
+                }
+                _ => result.push('\\'),
+            },
+            _ => result.push(ch),
+        }
+    }
+}
+
+/// Converts an argument into an lvalue, if it's something that can be assigned to
+fn as_lvalue(argument: &Expression) -> Option<LValue> {
+    match argument {
+        Expression::VarLookup(id) => Some(LValue::Variable(id.clone())),
+        Expression::SpecialVarLookup(special_var) => Some(LValue::Special(*special_var)),
+        Expression::ArrayLookup { id, subscripts } => Some(LValue::ArrayElement {
+            id: id.clone(),
+            subscripts: subscripts.clone(),
+        }),
+        Expression::ColumnNumber(column_expression) => {
+            Some(LValue::Column(column_expression.clone()))
+        }
+        _ => None,
+    }
+}
+
+fn builtin_match(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    check_arg_count("match", arguments, 2, 2);
+
+    let string = interpreter.eval_string(&arguments[0]);
+    let regex = eval_regex(interpreter, &arguments[1]);
+
+    // Like the other string functions, positions are counted in characters, starting at 1
+    let (start, length) = match regex.find(&string) {
+        Some(found) => (
+            string[..found.start()].chars().count() as f64 + 1.0,
+            found.as_str().chars().count() as f64,
+        ),
+        None => (0.0, -1.0),
+    };
+
+    // These are assigned like any other variable, so a function parameter named RSTART or
+    // RLENGTH hides the global
+    for (name, value) in [("RSTART", start), ("RLENGTH", length)] {
+        let place = interpreter.resolve_place(&LValue::Variable(Id(name.to_string())));
+        interpreter.assign(&place, Value::Num(value));
+    }
+
+    Value::Num(start)
+}
+
+fn tolower(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    check_arg_count("tolower", arguments, 1, 1);
+
+    Value::String(interpreter.eval_string(&arguments[0]).to_lowercase())
+}
+
+fn toupper(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    check_arg_count("toupper", arguments, 1, 1);
+
+    Value::String(interpreter.eval_string(&arguments[0]).to_uppercase())
+}
+
+fn builtin_sprintf(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    let argument_values: Vec<_> = arguments
+        .iter()
+        .map(|arg| interpreter.eval_exp(arg))
+        .collect();
+
+    match argument_values.split_first() {
+        Some((format, rest)) => {
+            let format = interpreter.convert_to_string(format.clone());
+            Value::String(sprintf(&format, rest))
+        }
+        None => panic!("sprintf requires a format argument"),
+    }
+}
+
+fn close(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    check_arg_count("close", arguments, 1, 1);
+
+    let stream_name = interpreter.eval_string(&arguments[0]);
+
+    // The name may refer to either an output stream or an input stream
+    let result = match interpreter.output_streams.close(&stream_name) {
+        -1 => interpreter.input_sources.close(&stream_name),
+        result => result,
+    };
+
+    Value::Num(result as f64)
+}
+
+fn fflush(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    check_arg_count("fflush", arguments, 0, 1);
+
+    let stream_name = arguments
+        .first()
+        .map(|stream_name| interpreter.eval_string(stream_name));
+
+    Value::Num(interpreter.output_streams.flush(stream_name.as_deref()) as f64)
+}
+
+/// Evaluates the single numeric argument of an arithmetic function
+fn eval_num_arg(name: &str, interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> f64 {
+    check_arg_count(name, arguments, 1, 1);
+
+    interpreter.eval_exp(&arguments[0]).to_num()
+}
+
+fn int(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    // Truncates towards zero, rather than rounding down
+    Value::Num(eval_num_arg("int", interpreter, arguments).trunc())
+}
+
+fn sqrt(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    Value::Num(eval_num_arg("sqrt", interpreter, arguments).sqrt())
+}
+
+fn exp(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    Value::Num(eval_num_arg("exp", interpreter, arguments).exp())
+}
+
+fn log(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    Value::Num(eval_num_arg("log", interpreter, arguments).ln())
+}
+
+fn sin(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    Value::Num(eval_num_arg("sin", interpreter, arguments).sin())
+}
+
+fn cos(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    Value::Num(eval_num_arg("cos", interpreter, arguments).cos())
+}
+
+fn atan2(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    check_arg_count("atan2", arguments, 2, 2);
+
+    let y = interpreter.eval_exp(&arguments[0]).to_num();
+    let x = interpreter.eval_exp(&arguments[1]).to_num();
+
+    Value::Num(y.atan2(x))
+}
+
+fn rand(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    check_arg_count("rand", arguments, 0, 0);
+
+    Value::Num(interpreter.random.next_f64())
+}
+
+fn srand(interpreter: &mut Interpreter, arguments: &[Box<Expression>]) -> Value {
+    check_arg_count("srand", arguments, 0, 1);
+
+    // Without a seed, the time of day is used. Either way, the previous seed is returned.
+    let previous_seed = match arguments.first() {
+        Some(seed) => {
+            let seed = interpreter.eval_exp(seed).to_num();
+            interpreter.random.reseed(seed)
+        }
+        None => interpreter.random.reseed_from_time(),
+    };
+
+    Value::Num(previous_seed)
+}
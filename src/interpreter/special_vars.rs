@@ -0,0 +1,96 @@
+// Holds the special variables that control how records are read, split and printed. They're kept
+// in their own fields, rather than in the global variables, so that the interpreter doesn't need to
+// look them up by name for every record.
+
+use super::Value;
+use crate::ast::SpecialVar;
+
+/// The format used to convert numbers to strings, unless CONVFMT or OFMT says otherwise
+pub(super) const DEFAULT_NUMBER_FORMAT: &str = "%.6g";
+
+/// The values of the special variables other than NF, which start out with the defaults specified by POSIX
+pub(super) struct SpecialVars {
+    pub(super) fs: String,
+    pub(super) ofs: String,
+    pub(super) ors: String,
+    pub(super) rs: String,
+    pub(super) rt: String,
+    pub(super) nr: f64,
+    pub(super) fnr: f64,
+    pub(super) filename: String,
+    pub(super) subsep: String,
+    pub(super) convfmt: String,
+    pub(super) ofmt: String,
+}
+
+impl Default for SpecialVars {
+    fn default() -> Self {
+        SpecialVars {
+            fs: " ".to_string(),
+            ofs: " ".to_string(),
+            ors: "\n".to_string(),
+            rs: "\n".to_string(),
+            rt: String::new(),
+            nr: 0.0,
+            fnr: 0.0,
+            filename: String::new(),
+            subsep: "\x1c".to_string(),
+            convfmt: DEFAULT_NUMBER_FORMAT.to_string(),
+            ofmt: DEFAULT_NUMBER_FORMAT.to_string(),
+        }
+    }
+}
+
+impl SpecialVars {
+    pub(super) fn get(&self, special_var: SpecialVar) -> Value {
+        match special_var {
+            SpecialVar::Nr => Value::Num(self.nr),
+            SpecialVar::Fnr => Value::Num(self.fnr),
+            _ => Value::String(self.string_var(special_var).clone()),
+        }
+    }
+
+    pub(super) fn set(&mut self, special_var: SpecialVar, value: &Value) {
+        match special_var {
+            SpecialVar::Nr => self.nr = value.to_num(),
+            SpecialVar::Fnr => self.fnr = value.to_num(),
+            _ => {
+                // Numbers assigned to the string variables are converted like any other string
+                let string = value.to_string_with_format(&self.convfmt);
+                *self.string_var_mut(special_var) = string;
+            }
+        }
+    }
+
+    fn string_var(&self, special_var: SpecialVar) -> &String {
+        match special_var {
+            SpecialVar::Fs => &self.fs,
+            SpecialVar::Ofs => &self.ofs,
+            SpecialVar::Ors => &self.ors,
+            SpecialVar::Rs => &self.rs,
+            SpecialVar::Rt => &self.rt,
+            SpecialVar::Filename => &self.filename,
+            SpecialVar::Subsep => &self.subsep,
+            SpecialVar::Convfmt => &self.convfmt,
+            SpecialVar::Ofmt => &self.ofmt,
+            SpecialVar::Nr | SpecialVar::Fnr => unreachable!("NR and FNR are numbers"),
+            SpecialVar::Nf => unreachable!("NF is computed from the current record"),
+        }
+    }
+
+    fn string_var_mut(&mut self, special_var: SpecialVar) -> &mut String {
+        match special_var {
+            SpecialVar::Fs => &mut self.fs,
+            SpecialVar::Ofs => &mut self.ofs,
+            SpecialVar::Ors => &mut self.ors,
+            SpecialVar::Rs => &mut self.rs,
+            SpecialVar::Rt => &mut self.rt,
+            SpecialVar::Filename => &mut self.filename,
+            SpecialVar::Subsep => &mut self.subsep,
+            SpecialVar::Convfmt => &mut self.convfmt,
+            SpecialVar::Ofmt => &mut self.ofmt,
+            SpecialVar::Nr | SpecialVar::Fnr => unreachable!("NR and FNR are numbers"),
+            SpecialVar::Nf => unreachable!("NF is computed from the current record"),
+        }
+    }
+}
@@ -1,7 +1,19 @@
 mod ast;
 mod parser;
 mod interpreter;
+mod format;
+mod output;
+mod input;
+mod regex_cache;
+mod random;
+mod fields;
 
 pub use ast::*;
 pub use parser::*;
 pub use interpreter::*;
+pub use format::*;
+pub use output::*;
+pub use input::*;
+pub use regex_cache::*;
+pub use random::*;
+pub use fields::*;
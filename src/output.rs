@@ -0,0 +1,167 @@
+// Keeps track of the files and commands that `print` and `printf` can redirect their output to.
+
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    process::{Child, Command, Stdio},
+};
+
+use crate::ast::RedirectionKind;
+
+enum OutputStream {
+    Stdout,
+    Stderr,
+    File(BufWriter<File>),
+    Pipe(Child),
+}
+
+/// The table of open output streams, keyed by the file name or command used to open them
+#[derive(Default)]
+pub struct OutputStreams {
+    streams: HashMap<String, OutputStream>,
+}
+
+impl OutputStreams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes to stdout
+    pub fn write_stdout(&mut self, text: &str) {
+        io::stdout()
+            .write_all(text.as_bytes())
+            .expect("Failed to write to stdout");
+    }
+
+    /// Writes to the stream with the given name, opening it if it isn't already open
+    pub fn write(&mut self, kind: RedirectionKind, name: &str, text: &str) {
+        if !self.streams.contains_key(name) {
+            let stream = Self::open(kind, name);
+            self.streams.insert(name.to_string(), stream);
+        }
+
+        let result = match self.streams.get_mut(name).expect("Stream was just opened") {
+            OutputStream::Stdout => io::stdout().write_all(text.as_bytes()),
+            OutputStream::Stderr => io::stderr().write_all(text.as_bytes()),
+            OutputStream::File(writer) => writer.write_all(text.as_bytes()),
+            OutputStream::Pipe(child) => child
+                .stdin
+                .as_mut()
+                .expect("Pipe has no stdin")
+                .write_all(text.as_bytes()),
+        };
+
+        if let Err(err) = result {
+            panic!("Failed to write to \"{}\": {}", name, err);
+        }
+    }
+
+    fn open(kind: RedirectionKind, name: &str) -> OutputStream {
+        match (kind, name) {
+            (RedirectionKind::Truncate | RedirectionKind::Append, "/dev/stdout" | "-") => {
+                OutputStream::Stdout
+            }
+            (RedirectionKind::Truncate | RedirectionKind::Append, "/dev/stderr") => {
+                OutputStream::Stderr
+            }
+            (RedirectionKind::Truncate, _) => match File::create(name) {
+                Ok(file) => OutputStream::File(BufWriter::new(file)),
+                Err(err) => panic!("Cannot open \"{}\" for output: {}", name, err),
+            },
+            (RedirectionKind::Append, _) => {
+                match OpenOptions::new().append(true).create(true).open(name) {
+                    Ok(file) => OutputStream::File(BufWriter::new(file)),
+                    Err(err) => panic!("Cannot open \"{}\" for output: {}", name, err),
+                }
+            }
+            (RedirectionKind::Pipe, _) => {
+                // Anything we've already printed should come before the command's output
+                io::stdout().flush().expect("Failed to flush stdout");
+
+                let child = Command::new("sh")
+                    .arg("-c")
+                    .arg(name)
+                    .stdin(Stdio::piped())
+                    .spawn();
+
+                match child {
+                    Ok(child) => OutputStream::Pipe(child),
+                    Err(err) => panic!("Cannot run command \"{}\": {}", name, err),
+                }
+            }
+        }
+    }
+
+    /// Flushes the stream with the given name, or every stream if no name is given. Returns 0 on
+    /// success and -1 if the stream isn't open or can't be flushed.
+    pub fn flush(&mut self, name: Option<&str>) -> i32 {
+        let result = match name {
+            Some(name) => match self.streams.get_mut(name) {
+                Some(stream) => Self::flush_stream(stream),
+                None => return -1,
+            },
+            None => {
+                let mut result = io::stdout().flush();
+
+                for stream in self.streams.values_mut() {
+                    result = result.and(Self::flush_stream(stream));
+                }
+
+                result
+            }
+        };
+
+        if result.is_ok() {
+            0
+        } else {
+            -1
+        }
+    }
+
+    fn flush_stream(stream: &mut OutputStream) -> io::Result<()> {
+        match stream {
+            OutputStream::Stdout => io::stdout().flush(),
+            OutputStream::Stderr => io::stderr().flush(),
+            OutputStream::File(writer) => writer.flush(),
+            OutputStream::Pipe(child) => child.stdin.as_mut().expect("Pipe has no stdin").flush(),
+        }
+    }
+
+    /// Closes the stream with the given name. Returns -1 if it isn't open, the exit status of the
+    /// command for a pipe, and 0 otherwise.
+    pub fn close(&mut self, name: &str) -> i32 {
+        match self.streams.remove(name) {
+            Some(stream) => Self::close_stream(stream),
+            None => -1,
+        }
+    }
+
+    /// Flushes and closes every stream, such as when the program exits
+    pub fn close_all(&mut self) {
+        io::stdout().flush().expect("Failed to flush stdout");
+
+        for (_, stream) in self.streams.drain() {
+            Self::close_stream(stream);
+        }
+    }
+
+    fn close_stream(stream: OutputStream) -> i32 {
+        match stream {
+            OutputStream::Stdout => io::stdout().flush().map_or(-1, |_| 0),
+            OutputStream::Stderr => io::stderr().flush().map_or(-1, |_| 0),
+            OutputStream::File(mut writer) => writer.flush().map_or(-1, |_| 0),
+            OutputStream::Pipe(mut child) => {
+                io::stdout().flush().expect("Failed to flush stdout");
+
+                // Closing the command's stdin lets it know that there's no more input
+                drop(child.stdin.take());
+
+                match child.wait() {
+                    Ok(status) => status.code().unwrap_or(-1),
+                    Err(_) => -1,
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,10 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, iter::Peekable, rc::Rc, str::Chars};
 
-use pest::{error::Error, iterators::Pair, Parser};
+use pest::{
+    error::{Error, ErrorVariant},
+    iterators::Pair,
+    Parser,
+};
 use pest_derive::Parser;
 use regex::Regex;
 
@@ -16,6 +20,8 @@ macro_rules! panic_unexpected_rule {
     };
 }
 
+// Pest's error type is large, but it's only ever produced once per parse
+#[allow(clippy::result_large_err)]
 pub fn parse(source: &str) -> Result<Program, Error<Rule>> {
     let mut program = Program {
         pattern_blocks: vec![],
@@ -26,6 +32,10 @@ pub fn parse(source: &str) -> Result<Program, Error<Rule>> {
 
     let start = pairs.next().unwrap();
 
+    check_loop_statements(start.clone(), false)?;
+    check_grouped_subscripts(start.clone())?;
+    check_lvalues(start.clone())?;
+
     for pair in start.into_inner() {
         match pair.as_rule() {
             Rule::Program => (),
@@ -53,6 +63,141 @@ pub fn parse(source: &str) -> Result<Program, Error<Rule>> {
     Ok(program)
 }
 
+/// Checks that `break` and `continue` are only used inside of loops
+#[allow(clippy::result_large_err)]
+fn check_loop_statements(pair: Pair<Rule>, in_loop: bool) -> Result<(), Error<Rule>> {
+    let in_loop = match pair.as_rule() {
+        Rule::WhileStatement
+        | Rule::DoWhileStatement
+        | Rule::ForStatement
+        | Rule::ForInStatement => true,
+        Rule::BreakStatement | Rule::ContinueStatement if !in_loop => {
+            return Err(Error::new_from_span(
+                ErrorVariant::CustomError {
+                    message: format!("{} used outside of a loop", pair.as_str()),
+                },
+                pair.as_span(),
+            ));
+        }
+        _ => in_loop,
+    };
+
+    for inner_pair in pair.into_inner() {
+        check_loop_statements(inner_pair, in_loop)?;
+    }
+
+    Ok(())
+}
+
+/// Checks that only variables, array elements and columns are assigned to
+#[allow(clippy::result_large_err)]
+fn check_lvalues(pair: Pair<Rule>) -> Result<(), Error<Rule>> {
+    if pair.as_rule() == Rule::Expression1 {
+        let mut inner_pairs = pair.clone().into_inner();
+        let target = inner_pairs.next().expect("Ran out of pairs");
+
+        if inner_pairs.next().is_some() && !is_lvalue(target.clone()) {
+            return Err(Error::new_from_span(
+                ErrorVariant::CustomError {
+                    message: "only a variable, array element or column can be assigned to"
+                        .to_string(),
+                },
+                target.as_span(),
+            ));
+        }
+    }
+
+    for inner_pair in pair.into_inner() {
+        check_lvalues(inner_pair)?;
+    }
+
+    Ok(())
+}
+
+/// Returns whether an expression is just a variable, array element or column
+fn is_lvalue(pair: Pair<Rule>) -> bool {
+    let mut curr_pair = pair;
+
+    loop {
+        match curr_pair.as_rule() {
+            Rule::LValue => return true,
+            // Parenthesized lvalues like `(x)` can't be assigned to
+            Rule::Atom => return false,
+            _ => (),
+        }
+
+        // Expressions with a single child are just wrappers around a higher-precedence expression
+        let mut inner_pairs = curr_pair.into_inner();
+
+        match (inner_pairs.next(), inner_pairs.next()) {
+            (Some(inner_pair), None) => curr_pair = inner_pair,
+            _ => return false,
+        }
+    }
+}
+
+/// Checks that parenthesized lists of expressions are only used as the subscripts of `in`, and not
+/// as part of a larger expression like `x + (i, j) in arr`
+#[allow(clippy::result_large_err)]
+fn check_grouped_subscripts(pair: Pair<Rule>) -> Result<(), Error<Rule>> {
+    match pair.as_rule() {
+        Rule::Grouping if pair.clone().into_inner().count() > 1 => {
+            return Err(Error::new_from_span(
+                ErrorVariant::CustomError {
+                    message: "a parenthesized list of expressions can only be used with `in`"
+                        .to_string(),
+                },
+                pair.as_span(),
+            ));
+        }
+        Rule::Expression5 => {
+            let mut inner_pairs = pair.clone().into_inner();
+            let first_pair = inner_pairs.next().expect("Ran out of pairs");
+
+            // The remaining pairs are the names of the arrays
+            if inner_pairs.next().is_some() {
+                if let Some(grouping) = find_grouped_subscripts(first_pair) {
+                    for subscript in grouping.into_inner() {
+                        check_grouped_subscripts(subscript)?;
+                    }
+
+                    return Ok(());
+                }
+            }
+        }
+        _ => (),
+    }
+
+    for inner_pair in pair.into_inner() {
+        check_grouped_subscripts(inner_pair)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the grouping that makes up the whole of an expression, if it's a list of subscripts
+fn find_grouped_subscripts(pair: Pair<Rule>) -> Option<Pair<Rule>> {
+    let mut curr_pair = pair;
+
+    // Expressions with a single child are just wrappers around a higher-precedence expression
+    while curr_pair.as_rule() != Rule::Grouping {
+        let mut inner_pairs = curr_pair.into_inner();
+        let inner_pair = inner_pairs.next()?;
+
+        if inner_pairs.next().is_some() {
+            return None;
+        }
+
+        curr_pair = inner_pair;
+    }
+
+    if curr_pair.clone().into_inner().count() > 1 {
+        Some(curr_pair)
+    } else {
+        None
+    }
+}
+
 fn build_function_def(pair: Pair<Rule>) -> FunctionDef {
     assert_eq!(pair.as_rule(), Rule::FunctionDef);
 
@@ -97,11 +242,15 @@ fn build_pattern(pair: Pair<Rule>) -> Pattern {
 
     let span = pair.as_str();
 
-    if let Some(inner_pair) = pair.into_inner().next() {
-        match inner_pair.as_rule() {
-            Rule::Expression => Pattern::Expression(build_expression(inner_pair)),
-            _ => panic_unexpected_rule!(inner_pair),
-        }
+    let mut expressions: Vec<_> = pair.into_inner().map(build_expression).collect();
+
+    if expressions.len() == 2 {
+        let end = expressions.pop().expect("Ran out of expressions");
+        let start = expressions.pop().expect("Ran out of expressions");
+
+        Pattern::Range { start, end }
+    } else if let Some(expression) = expressions.pop() {
+        Pattern::Expression(expression)
     } else {
         match span {
             "BEGIN" => Pattern::Begin,
@@ -115,9 +264,94 @@ fn build_regex(pair: Pair<Rule>) -> Regex {
     assert_eq!(pair.as_rule(), Rule::Regex);
 
     let span = pair.as_str();
-    let regex_str = &span[1..span.len() - 1];
+    let regex_str = translate_regex_escapes(&span[1..span.len() - 1]);
+
+    Regex::new(&regex_str).expect("Unable to compile regex")
+}
+
+/// Replaces the escape sequences in a string literal with the characters they represent
+pub fn unescape_string(literal: &str) -> String {
+    let mut result = String::new();
+    let mut chars = literal.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+
+        match chars.next() {
+            Some('"') => result.push('"'),
+            Some('\\') => result.push('\\'),
+            Some('/') => result.push('/'),
+            Some('a') => result.push('\x07'),
+            Some('b') => result.push('\x08'),
+            Some('f') => result.push('\x0c'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some('v') => result.push('\x0b'),
+            Some(digit @ '0'..='7') => result.push(parse_octal_escape(digit, &mut chars)),
+            // Other escape sequences are undefined by POSIX, so we leave them as they are
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
 
-    Regex::new(regex_str).expect("Unable to compile regex")
+    result
+}
+
+/// Converts the escape sequences in a regex that awk and the regex crate disagree on,
+/// leaving the rest for the regex crate to handle
+pub(crate) fn translate_regex_escapes(literal: &str) -> String {
+    let mut result = String::new();
+    let mut chars = literal.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+
+        match chars.next() {
+            Some('/') => result.push('/'),
+            Some('"') => result.push('"'),
+            // In awk, these are characters rather than a bell and a word boundary
+            Some('a') => result.push_str("\\x07"),
+            Some('b') => result.push_str("\\x08"),
+            Some(digit @ '0'..='7') => {
+                let escaped = parse_octal_escape(digit, &mut chars);
+                result.push_str(&regex::escape(&escaped.to_string()));
+            }
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+/// Parses an escape sequence of up to three octal digits, like `\033`
+fn parse_octal_escape(first_digit: char, chars: &mut Peekable<Chars>) -> char {
+    let mut code = first_digit.to_digit(8).expect("Not an octal digit");
+
+    for _ in 0..2 {
+        match chars.peek().and_then(|ch| ch.to_digit(8)) {
+            Some(digit) => {
+                code = code * 8 + digit;
+                chars.next();
+            }
+            None => break,
+        }
+    }
+
+    char::from_u32(code).expect("Octal escapes are always valid characters")
 }
 
 fn build_block(pair: Pair<Rule>) -> Block {
@@ -133,46 +367,63 @@ fn build_block(pair: Pair<Rule>) -> Block {
 }
 
 fn build_statement(pair: Pair<Rule>) -> Statement {
-    for inner_pair in pair.into_inner() {
-        match inner_pair.as_rule() {
-            Rule::PrintStatement => {
-                let mut inner_iter = inner_pair.into_inner();
-                let expression_pair = inner_iter.next().unwrap();
-                let expression = build_expression(expression_pair);
+    let inner_pair = pair.into_inner().next().expect("No inner pair");
 
-                return Statement::PrintStatement(PrintStatement { expression });
-            }
-            Rule::LocalVarStatement => {
-                return build_local_var_statement(inner_pair);
-            }
-            Rule::IfStatement => {
-                return build_if_statement(inner_pair);
-            }
-            Rule::WhileStatement => {
-                return build_while_statement(inner_pair);
-            }
-            Rule::ForStatement => {
-                return build_for_statement(inner_pair);
-            }
-            Rule::ReturnStatement => {
-                let mut inner_iter = inner_pair.into_inner();
-                let expression = build_expression(inner_iter.next().expect("Ran out of pairs"));
+    match inner_pair.as_rule() {
+        Rule::PrintStatement => {
+            let (expressions, redirection) = build_output_list(inner_pair);
 
-                return Statement::ReturnStatement(expression);
-            }
-            Rule::ExpressionStatement => {
-                let inner_expression_pair = inner_pair.into_inner().next().expect("No inner pair");
+            Statement::PrintStatement(PrintStatement {
+                expressions,
+                redirection,
+            })
+        }
+        Rule::PrintfStatement => {
+            let (mut arguments, redirection) = build_output_list(inner_pair);
+            let format = arguments.remove(0);
+
+            Statement::PrintfStatement(PrintfStatement {
+                format,
+                arguments,
+                redirection,
+            })
+        }
+        Rule::LocalVarStatement => build_local_var_statement(inner_pair),
+        Rule::IfStatement => build_if_statement(inner_pair),
+        Rule::WhileStatement => build_while_statement(inner_pair),
+        Rule::DoWhileStatement => build_do_while_statement(inner_pair),
+        Rule::ForInStatement => build_for_in_statement(inner_pair),
+        Rule::ForStatement => build_for_statement(inner_pair),
+        Rule::ReturnStatement => {
+            let mut inner_iter = inner_pair.into_inner();
+            let expression = build_expression(inner_iter.next().expect("Ran out of pairs"));
+
+            Statement::ReturnStatement(expression)
+        }
+        Rule::DeleteStatement => {
+            let mut inner_iter = inner_pair.into_inner();
+            let array = build_id(inner_iter.next().expect("Ran out of pairs"));
+            let subscripts = inner_iter.next().map(build_subscripts);
 
-                return Statement::ExpressionStatement(build_expression(inner_expression_pair));
-            }
-            Rule::Block => {
-                return Statement::BlockStatement(build_block(inner_pair));
-            }
-            _ => panic_unexpected_rule!(inner_pair),
+            Statement::DeleteStatement { array, subscripts }
         }
-    }
+        Rule::NextStatement => Statement::NextStatement,
+        Rule::BreakStatement => Statement::BreakStatement,
+        Rule::ContinueStatement => Statement::ContinueStatement,
+        Rule::NextfileStatement => Statement::NextfileStatement,
+        Rule::ExitStatement => {
+            let status = inner_pair.into_inner().next().map(build_expression);
+
+            Statement::ExitStatement(status)
+        }
+        Rule::ExpressionStatement => {
+            let inner_expression_pair = inner_pair.into_inner().next().expect("No inner pair");
 
-    unreachable!()
+            Statement::ExpressionStatement(build_expression(inner_expression_pair))
+        }
+        Rule::Block => Statement::BlockStatement(build_block(inner_pair)),
+        _ => panic_unexpected_rule!(inner_pair),
+    }
 }
 
 fn build_local_var_statement(pair: Pair<Rule>) -> Statement {
@@ -220,6 +471,32 @@ fn build_while_statement(pair: Pair<Rule>) -> Statement {
     Statement::WhileStatement { condition, body }
 }
 
+fn build_do_while_statement(pair: Pair<Rule>) -> Statement {
+    assert_eq!(pair.as_rule(), Rule::DoWhileStatement);
+
+    let mut inner_pairs = pair.into_inner();
+
+    let body = Box::new(build_statement(inner_pairs.next().expect("No more pairs")));
+
+    let condition = build_expression(inner_pairs.next().expect("No more pairs"));
+
+    Statement::DoWhileStatement { body, condition }
+}
+
+fn build_for_in_statement(pair: Pair<Rule>) -> Statement {
+    assert_eq!(pair.as_rule(), Rule::ForInStatement);
+
+    let mut inner_pairs = pair.into_inner();
+
+    let key = build_id(inner_pairs.next().expect("No more pairs"));
+
+    let array = build_id(inner_pairs.next().expect("No more pairs"));
+
+    let body = Box::new(build_statement(inner_pairs.next().expect("No more pairs")));
+
+    Statement::ForInStatement { key, array, body }
+}
+
 fn build_for_statement(pair: Pair<Rule>) -> Statement {
     assert_eq!(pair.as_rule(), Rule::ForStatement);
 
@@ -270,6 +547,102 @@ fn build_for_statement(pair: Pair<Rule>) -> Statement {
     }
 }
 
+/// Builds the expressions of a print or printf statement, along with where to send their output
+fn build_output_list(pair: Pair<Rule>) -> (Vec<Expression>, Option<OutputRedirection>) {
+    let mut expression_pairs = vec![];
+    let mut redirection = None;
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::Expression => expression_pairs.push(inner_pair),
+            Rule::OutputRedirection => redirection = Some(build_output_redirection(inner_pair)),
+            _ => panic_unexpected_rule!(inner_pair),
+        }
+    }
+
+    let last_pair = expression_pairs.pop();
+
+    let mut expressions: Vec<_> = expression_pairs.into_iter().map(build_expression).collect();
+
+    if let Some(last_pair) = last_pair {
+        // In `print x > "file"`, the grammar parses `x > "file"` as a comparison
+        match split_redirection_comparison(last_pair.clone()) {
+            Some((expression, comparison_redirection)) if redirection.is_none() => {
+                expressions.push(expression);
+                redirection = Some(comparison_redirection);
+            }
+            _ => expressions.push(build_expression(last_pair)),
+        }
+    }
+
+    (expressions, redirection)
+}
+
+fn build_output_redirection(pair: Pair<Rule>) -> OutputRedirection {
+    assert_eq!(pair.as_rule(), Rule::OutputRedirection);
+
+    let mut inner_pairs = pair.into_inner();
+
+    let sign_pair = inner_pairs.next().expect("Ran out of pairs");
+    let kind = match sign_pair.as_rule() {
+        Rule::GreaterThanSign => RedirectionKind::Truncate,
+        Rule::AppendSign => RedirectionKind::Append,
+        Rule::PipeSign => RedirectionKind::Pipe,
+        _ => panic_unexpected_rule!(sign_pair),
+    };
+
+    let target = build_expression8(inner_pairs.next().expect("Ran out of pairs"));
+
+    OutputRedirection { kind, target }
+}
+
+/// If an expression is an unparenthesized `>` comparison, splits it into the expression on the
+/// left and a redirection to the expression on the right
+fn split_redirection_comparison(pair: Pair<Rule>) -> Option<(Expression, OutputRedirection)> {
+    let mut curr_pair = pair;
+
+    // Expressions with a single child are just wrappers around a higher-precedence expression
+    while curr_pair.as_rule() != Rule::Expression7 {
+        match curr_pair.as_rule() {
+            Rule::Expression
+            | Rule::Expression1
+            | Rule::Expression2
+            | Rule::Expression3
+            | Rule::Expression4
+            | Rule::Expression5
+            | Rule::Expression6 => {
+                let mut inner_pairs = curr_pair.into_inner();
+                let inner_pair = inner_pairs.next()?;
+
+                if inner_pairs.next().is_some() {
+                    return None;
+                }
+
+                curr_pair = inner_pair;
+            }
+            _ => return None,
+        }
+    }
+
+    let mut operands: Vec<Pair<Rule>> = curr_pair.into_inner().collect();
+
+    if operands.len() != 3 || operands[1].as_rule() != Rule::GreaterThanSign {
+        return None;
+    }
+
+    let target = build_expression8(operands.pop().expect("Ran out of pairs"));
+    operands.pop();
+    let expression = build_expression8(operands.pop().expect("Ran out of pairs"));
+
+    Some((
+        expression,
+        OutputRedirection {
+            kind: RedirectionKind::Truncate,
+            target,
+        },
+    ))
+}
+
 fn build_expression(pair: Pair<Rule>) -> Expression {
     assert_eq!(pair.as_rule(), Rule::Expression);
 
@@ -295,27 +668,47 @@ fn build_expression1(pair: Pair<Rule>) -> Expression {
     } else {
         assert!(inner_pairs.len() == 3);
 
-        let rhs_expression = build_expression2(inner_pairs.pop().expect("Ran out of pairs"));
+        let rhs_expression = build_expression1(inner_pairs.pop().expect("Ran out of pairs"));
         let rule_sign = {
             let inner_pair = inner_pairs.pop().expect("Ran out of pairs");
             match inner_pair.as_rule() {
                 Rule::EqualSign => Expression::Assign,
                 Rule::PlusEqualsSign => Expression::PlusAssign,
+                Rule::MinusEqualsSign => Expression::MinusAssign,
+                Rule::TimesEqualsSign => Expression::TimesAssign,
+                Rule::DivEqualsSign => Expression::DivAssign,
+                Rule::PercentEqualsSign => Expression::ModuloAssign,
+                Rule::PowEqualsSign => Expression::PowAssign,
                 _ => panic_unexpected_rule!(inner_pair),
             }
         };
-        let id = build_id(inner_pairs.pop().expect("Ran out of pairs"));
+        let lvalue = into_lvalue(build_expression2(
+            inner_pairs.pop().expect("Ran out of pairs"),
+        ));
 
-        rule_sign(id, Box::new(rhs_expression))
+        rule_sign(lvalue, Box::new(rhs_expression))
     }
 }
 
 fn build_expression2(pair: Pair<Rule>) -> Expression {
     assert_eq!(pair.as_rule(), Rule::Expression2);
 
-    let inner_pair = pair.into_inner().next().expect("inner_pairs is empty");
+    let mut inner_pairs = pair.into_inner();
+
+    let condition = build_expression3(inner_pairs.next().expect("inner_pairs is empty"));
+
+    if let Some(true_pair) = inner_pairs.next() {
+        let true_expression = build_expression2(true_pair);
+        let false_expression = build_expression2(inner_pairs.next().expect("Ran out of pairs"));
 
-    build_expression3(inner_pair)
+        Expression::Conditional {
+            condition: Box::new(condition),
+            true_expression: Box::new(true_expression),
+            false_expression: Box::new(false_expression),
+        }
+    } else {
+        condition
+    }
 }
 
 fn build_expression3(pair: Pair<Rule>) -> Expression {
@@ -349,9 +742,26 @@ fn build_expression4(pair: Pair<Rule>) -> Expression {
 fn build_expression5(pair: Pair<Rule>) -> Expression {
     assert_eq!(pair.as_rule(), Rule::Expression5);
 
-    let inner_pair = pair.into_inner().next().expect("Ran out of pairs");
+    let mut inner_pairs = pair.into_inner();
+
+    let first_pair = inner_pairs.next().expect("Ran out of pairs");
 
-    build_expression6(inner_pair)
+    let Some(array_pair) = inner_pairs.next() else {
+        return build_expression6(first_pair);
+    };
+
+    let subscripts = match find_grouped_subscripts(first_pair.clone()) {
+        Some(grouping) => grouping.into_inner().map(build_expression).collect(),
+        None => vec![build_expression6(first_pair)],
+    };
+    let array = build_id(array_pair);
+
+    inner_pairs.fold(Expression::In { subscripts, array }, |acc, array_pair| {
+        Expression::In {
+            subscripts: vec![acc],
+            array: build_id(array_pair),
+        }
+    })
 }
 
 fn build_expression6(pair: Pair<Rule>) -> Expression {
@@ -405,12 +815,30 @@ fn build_expression7(pair: Pair<Rule>) -> Expression {
 fn build_expression8(pair: Pair<Rule>) -> Expression {
     assert_eq!(pair.as_rule(), Rule::Expression8);
 
-    let operands: Vec<_> = pair.into_inner().map(build_expression9).collect();
+    let mut operands = vec![];
+    let mut getline_lvalues = vec![];
+
+    for inner_pair in pair.into_inner() {
+        match inner_pair.as_rule() {
+            Rule::Expression9 => operands.push(build_expression9(inner_pair)),
+            Rule::PipeSign => (),
+            Rule::SimpleGetline => getline_lvalues.push(build_simple_getline(inner_pair)),
+            _ => panic_unexpected_rule!(inner_pair),
+        }
+    }
 
-    operands[1..]
+    let concatenation = operands[1..]
         .iter()
         .fold(operands[0].clone(), |acc, operand| {
             Expression::Concatenate(Box::new(acc), Box::new(operand.clone()))
+        });
+
+    // Each `| getline` reads from the output of the command to its left
+    getline_lvalues
+        .into_iter()
+        .fold(concatenation, |command, lvalue| Expression::Getline {
+            source: GetlineSource::Command(Box::new(command)),
+            lvalue,
         })
 }
 
@@ -464,7 +892,7 @@ fn build_expression10(pair: Pair<Rule>) -> Expression {
             Rule::PercentSign => {
                 rule_sign = Some(Expression::Modulo);
             }
-            Rule::Atom => operands.push(build_atom(inner_pair)),
+            Rule::Expression11 => operands.push(build_expression11(inner_pair)),
             _ => panic_unexpected_rule!(inner_pair),
         }
     }
@@ -479,43 +907,226 @@ fn build_expression10(pair: Pair<Rule>) -> Expression {
     }
 }
 
+fn build_expression11(pair: Pair<Rule>) -> Expression {
+    assert_eq!(pair.as_rule(), Rule::Expression11);
+
+    let mut inner_pairs = pair.into_inner();
+
+    let first_pair = inner_pairs.next().expect("Ran out of pairs");
+
+    let rule_sign = match first_pair.as_rule() {
+        Rule::Expression12 => return build_expression12(first_pair),
+        Rule::NotSign => Expression::LogicalNot,
+        Rule::MinusSign => Expression::UnaryMinus,
+        Rule::PlusSign => Expression::UnaryPlus,
+        _ => panic_unexpected_rule!(first_pair),
+    };
+
+    let operand = build_expression11(inner_pairs.next().expect("Ran out of pairs"));
+
+    rule_sign(Box::new(operand))
+}
+
+fn build_expression12(pair: Pair<Rule>) -> Expression {
+    assert_eq!(pair.as_rule(), Rule::Expression12);
+
+    let mut inner_pairs = pair.into_inner();
+
+    let base = build_expression13(inner_pairs.next().expect("Ran out of pairs"));
+
+    if let Some(pow_sign_pair) = inner_pairs.next() {
+        assert_eq!(pow_sign_pair.as_rule(), Rule::PowSign);
+
+        // Exponentiation is right-associative, which the grammar handles for us
+        let exponent = build_expression11(inner_pairs.next().expect("Ran out of pairs"));
+
+        Expression::Pow(Box::new(base), Box::new(exponent))
+    } else {
+        base
+    }
+}
+
+fn build_expression13(pair: Pair<Rule>) -> Expression {
+    assert_eq!(pair.as_rule(), Rule::Expression13);
+
+    let mut inner_pairs = pair.into_inner();
+
+    let first_pair = inner_pairs.next().expect("Ran out of pairs");
+
+    let rule_sign = match first_pair.as_rule() {
+        Rule::Expression14 => return build_expression14(first_pair),
+        Rule::IncrementSign => Expression::PreIncrement,
+        Rule::DecrementSign => Expression::PreDecrement,
+        _ => panic_unexpected_rule!(first_pair),
+    };
+
+    rule_sign(build_lvalue(inner_pairs.next().expect("Ran out of pairs")))
+}
+
+fn build_expression14(pair: Pair<Rule>) -> Expression {
+    assert_eq!(pair.as_rule(), Rule::Expression14);
+
+    let mut inner_pairs = pair.into_inner();
+
+    let first_pair = inner_pairs.next().expect("Ran out of pairs");
+
+    if first_pair.as_rule() == Rule::Atom {
+        return build_atom(first_pair);
+    }
+
+    let lvalue = build_lvalue(first_pair);
+
+    let Some(sign_pair) = inner_pairs.next() else {
+        return lvalue_expression(lvalue);
+    };
+
+    match sign_pair.as_rule() {
+        Rule::IncrementSign => Expression::PostIncrement(lvalue),
+        Rule::DecrementSign => Expression::PostDecrement(lvalue),
+        _ => panic_unexpected_rule!(sign_pair),
+    }
+}
+
+/// Converts an lvalue that's only being read into the expression that reads it
+fn lvalue_expression(lvalue: LValue) -> Expression {
+    match lvalue {
+        LValue::Variable(id) => Expression::VarLookup(id),
+        LValue::Special(special_var) => Expression::SpecialVarLookup(special_var),
+        LValue::ArrayElement { id, subscripts } => Expression::ArrayLookup { id, subscripts },
+        LValue::Column(column_expression) => Expression::ColumnNumber(column_expression),
+    }
+}
+
+/// Converts an expression that's being assigned to into an lvalue. check_lvalues has already made
+/// sure that it's a variable, array element or column.
+fn into_lvalue(expression: Expression) -> LValue {
+    match expression {
+        Expression::VarLookup(id) => LValue::Variable(id),
+        Expression::SpecialVarLookup(special_var) => LValue::Special(special_var),
+        Expression::ArrayLookup { id, subscripts } => LValue::ArrayElement { id, subscripts },
+        Expression::ColumnNumber(column_expression) => LValue::Column(column_expression),
+        _ => unreachable!("Only lvalues are assigned to: {:?}", expression),
+    }
+}
+
 // The Atom rule is used to build an Expression
 fn build_atom(pair: Pair<Rule>) -> Expression {
     assert_eq!(pair.as_rule(), Rule::Atom);
 
-    for pair in pair.into_inner() {
-        let s = pair.as_str();
-        match pair.as_rule() {
-            Rule::String => {
-                return Expression::String {
-                    value: s[1..s.len() - 1].to_string(),
-                };
-            }
-            Rule::ColumnNumber => {
-                let column_num = s[1..].parse().unwrap();
-                return Expression::ColumnNumber(column_num);
-            }
-            Rule::FunctionCall => {
-                return build_function_call(pair);
-            }
-            Rule::VarLookup => {
-                let inner_id_pair = pair.into_inner().next().expect("No inner pair");
-                return Expression::VarLookup(build_id(inner_id_pair));
-            }
-            Rule::Num => {
-                return build_num(pair);
-            }
-            Rule::Regex => {
-                return Expression::Regex(build_regex(pair));
-            }
-            Rule::Expression => {
-                return build_expression(pair);
-            }
-            _ => panic_unexpected_rule!(pair),
+    let pair = pair.into_inner().next().expect("No inner pair");
+    let s = pair.as_str();
+
+    match pair.as_rule() {
+        Rule::String => Expression::String {
+            value: unescape_string(&s[1..s.len() - 1]),
+        },
+        Rule::ColumnNumber => Expression::ColumnNumber(Box::new(build_column_number(pair))),
+        Rule::FunctionCall => build_function_call(pair),
+        Rule::BuiltinFunctionCall => build_builtin_function_call(pair),
+        Rule::ArrayLookup => {
+            let (id, subscripts) = build_array_lookup(pair);
+            Expression::ArrayLookup { id, subscripts }
+        }
+        Rule::VarLookup => lvalue_expression(build_variable(pair)),
+        Rule::Num => build_num(pair),
+        Rule::Regex => Expression::Regex(Rc::new(build_regex(pair))),
+        Rule::Grouping => {
+            // Lists of subscripts are handled by build_expression5
+            let inner_pair = pair.into_inner().next().expect("No inner pair");
+            build_expression(inner_pair)
         }
+        Rule::Getline => build_getline(pair),
+        _ => panic_unexpected_rule!(pair),
     }
+}
+
+fn build_getline(pair: Pair<Rule>) -> Expression {
+    assert_eq!(pair.as_rule(), Rule::Getline);
+
+    let mut inner_pairs = pair.into_inner();
+
+    let lvalue = build_simple_getline(inner_pairs.next().expect("Ran out of pairs"));
+    let source = match inner_pairs.next() {
+        Some(file_pair) => GetlineSource::File(Box::new(build_atom(file_pair))),
+        None => GetlineSource::MainInput,
+    };
 
-    unreachable!()
+    Expression::Getline { source, lvalue }
+}
+
+/// Builds the optional lvalue that getline reads into
+fn build_simple_getline(pair: Pair<Rule>) -> Option<LValue> {
+    assert_eq!(pair.as_rule(), Rule::SimpleGetline);
+
+    pair.into_inner().next().map(build_lvalue)
+}
+
+fn build_lvalue(pair: Pair<Rule>) -> LValue {
+    assert_eq!(pair.as_rule(), Rule::LValue);
+
+    let inner_pair = pair.into_inner().next().expect("No inner pair");
+
+    match inner_pair.as_rule() {
+        Rule::ColumnNumber => LValue::Column(Box::new(build_column_number(inner_pair))),
+        Rule::VarLookup => build_variable(inner_pair),
+        Rule::ArrayLookup => {
+            let (id, subscripts) = build_array_lookup(inner_pair);
+            LValue::ArrayElement { id, subscripts }
+        }
+        _ => panic_unexpected_rule!(inner_pair),
+    }
+}
+
+/// Builds the expression for the number of the column being accessed
+fn build_column_number(pair: Pair<Rule>) -> Expression {
+    assert_eq!(pair.as_rule(), Rule::ColumnNumber);
+
+    let mut inner_pairs = pair.into_inner();
+
+    let first_pair = inner_pairs.next().expect("Ran out of pairs");
+
+    let rule_sign = match first_pair.as_rule() {
+        Rule::Atom => return build_atom(first_pair),
+        Rule::MinusSign | Rule::PlusSign => {
+            let operand = Box::new(build_atom(inner_pairs.next().expect("Ran out of pairs")));
+
+            return match first_pair.as_rule() {
+                Rule::MinusSign => Expression::UnaryMinus(operand),
+                _ => Expression::UnaryPlus(operand),
+            };
+        }
+        Rule::IncrementSign => Expression::PreIncrement,
+        Rule::DecrementSign => Expression::PreDecrement,
+        _ => panic_unexpected_rule!(first_pair),
+    };
+
+    rule_sign(build_lvalue(inner_pairs.next().expect("Ran out of pairs")))
+}
+
+/// Builds a variable, which may be one of the special variables
+fn build_variable(pair: Pair<Rule>) -> LValue {
+    assert_eq!(pair.as_rule(), Rule::VarLookup);
+
+    let id_pair = pair.into_inner().next().expect("No inner pair");
+
+    LValue::variable(id_pair.as_str())
+}
+
+fn build_array_lookup(pair: Pair<Rule>) -> (Id, Vec<Expression>) {
+    assert_eq!(pair.as_rule(), Rule::ArrayLookup);
+
+    let mut inner_pairs = pair.into_inner();
+
+    let id = build_id(inner_pairs.next().expect("Ran out of pairs"));
+    let subscripts = build_subscripts(inner_pairs.next().expect("Ran out of pairs"));
+
+    (id, subscripts)
+}
+
+fn build_subscripts(pair: Pair<Rule>) -> Vec<Expression> {
+    assert_eq!(pair.as_rule(), Rule::Subscripts);
+
+    pair.into_inner().map(build_expression).collect()
 }
 
 // The Num rule is used to build an Expression
@@ -537,9 +1148,7 @@ fn build_function_call(pair: Pair<Rule>) -> Expression {
         Rule::Id => {
             let name = build_id(first_pair);
 
-            let arguments: Vec<_> = inner_pairs
-                .map(|p| Box::new(build_expression(p)))
-                .collect();
+            let arguments: Vec<_> = inner_pairs.map(|p| Box::new(build_expression(p))).collect();
 
             Expression::FunctionCall { name, arguments }
         }
@@ -547,6 +1156,22 @@ fn build_function_call(pair: Pair<Rule>) -> Expression {
     }
 }
 
+fn build_builtin_function_call(pair: Pair<Rule>) -> Expression {
+    assert_eq!(pair.as_rule(), Rule::BuiltinFunctionCall);
+
+    let mut inner_pairs = pair.into_inner();
+
+    let name_pair = inner_pairs.next().expect("Ran out of pairs");
+    let name = match name_pair.as_rule() {
+        Rule::BuiltinName | Rule::BareLength => Id(name_pair.as_str().to_string()),
+        _ => panic_unexpected_rule!(name_pair),
+    };
+
+    let arguments: Vec<_> = inner_pairs.map(|p| Box::new(build_expression(p))).collect();
+
+    Expression::FunctionCall { name, arguments }
+}
+
 fn build_id(pair: Pair<Rule>) -> Id {
     assert_eq!(pair.as_rule(), Rule::Id);
 
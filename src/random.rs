@@ -0,0 +1,73 @@
+// Generates the pseudo-random numbers returned by `rand`. The sequence only depends on the seed,
+// so that `srand(n)` can be used to get reproducible results.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A seedable pseudo-random number generator, using the xorshift64* algorithm
+pub struct Random {
+    seed: f64,
+    state: u64,
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Random {
+    /// Creates a generator seeded with 0, so that `rand` without `srand` is reproducible
+    pub fn new() -> Self {
+        let mut random = Random {
+            seed: 0.0,
+            state: 0,
+        };
+        random.reseed(0.0);
+
+        random
+    }
+
+    /// Restarts the sequence from the given seed, returning the previous seed
+    pub fn reseed(&mut self, seed: f64) -> f64 {
+        let previous_seed = self.seed;
+
+        self.seed = seed;
+        // Scramble the seed so that nearby seeds give unrelated sequences. Xorshift needs a
+        // non-zero state.
+        self.state = match splitmix64(seed.to_bits()) {
+            0 => 1,
+            state => state,
+        };
+
+        previous_seed
+    }
+
+    /// Restarts the sequence from the current time of day, in seconds, returning the previous seed
+    pub fn reseed_from_time(&mut self) -> f64 {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+
+        self.reseed(seconds as f64)
+    }
+
+    /// Returns the next number in the sequence, which is at least 0 and less than 1
+    pub fn next_f64(&mut self) -> f64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+
+        let bits = self.state.wrapping_mul(0x2545_f491_4f6c_dd1d);
+
+        // Use the top 53 bits, which is as many as an f64 can represent exactly
+        (bits >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn splitmix64(value: u64) -> u64 {
+    let mut value = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+    value ^ (value >> 31)
+}
@@ -0,0 +1,69 @@
+// Caches the regular expressions compiled from strings at runtime, such as in `$0 ~ pattern`, so
+// that matching against every record doesn't recompile the same pattern each time.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    rc::Rc,
+};
+
+use regex::Regex;
+
+use crate::parser::translate_regex_escapes;
+
+// The number of patterns to keep compiled at once
+const CAPACITY: usize = 64;
+
+/// A least-recently-used cache of compiled regexes, keyed by their pattern text
+pub struct RegexCache {
+    // Each regex is shared rather than cloned, since a clone doesn't share the original's cache of
+    // matching state
+    regexes: HashMap<String, Rc<Regex>>,
+    // Patterns from least recently used to most recently used
+    usage_order: VecDeque<String>,
+}
+
+impl Default for RegexCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RegexCache {
+    pub fn new() -> Self {
+        RegexCache {
+            regexes: HashMap::new(),
+            usage_order: VecDeque::new(),
+        }
+    }
+
+    /// Returns the compiled regex for a pattern, compiling it if it isn't already cached
+    pub fn get(&mut self, pattern: &str) -> Result<Rc<Regex>, regex::Error> {
+        if let Some(regex) = self.regexes.get(pattern) {
+            let regex = Rc::clone(regex);
+
+            // Mark the pattern as the most recently used
+            if let Some(position) = self.usage_order.iter().position(|used| used == pattern) {
+                let used = self
+                    .usage_order
+                    .remove(position)
+                    .expect("Position is in bounds");
+                self.usage_order.push_back(used);
+            }
+
+            return Ok(regex);
+        }
+
+        let regex = Rc::new(Regex::new(&translate_regex_escapes(pattern))?);
+
+        if self.regexes.len() >= CAPACITY {
+            if let Some(least_used) = self.usage_order.pop_front() {
+                self.regexes.remove(&least_used);
+            }
+        }
+
+        self.regexes.insert(pattern.to_string(), Rc::clone(&regex));
+        self.usage_order.push_back(pattern.to_string());
+
+        Ok(regex)
+    }
+}
@@ -0,0 +1,35 @@
+{
+  count[$2] += 1
+}
+
+END {
+  for (unit in count) {
+    print unit " appears " count[unit] " times"
+  }
+
+  if ("C" in count) {
+    print "C is in count"
+  }
+
+  if ("K" in count) {
+    print "K is in count"
+  }
+
+  delete count["unit"]
+
+  for (unit in count) {
+    print "After delete: " unit
+  }
+
+  delete count
+
+  for (unit in count) {
+    print "After deleting everything: " unit
+  }
+
+  # Referencing an element creates it
+  print "Missing element: " count["missing"]
+  if ("missing" in count) {
+    print "missing is now in count"
+  }
+}
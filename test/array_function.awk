@@ -0,0 +1,21 @@
+# Arrays are passed to functions by reference
+function fill(arr, n) {
+  for (i = 1; i < n + 1; i += 1) {
+    arr[i] = i * i
+  }
+}
+
+function split_words(string, words) {
+  return split(string, words)
+}
+
+END {
+  # An untyped variable becomes an array if the function uses it as one
+  fill(squares, 3)
+
+  for (k in squares) {
+    print k " squared is " squares[k]
+  }
+
+  print split_words("p q", words) " words, the last is " words[2]
+}
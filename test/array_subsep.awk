@@ -0,0 +1,34 @@
+# Builds a small multiplication table, indexed by two subscripts
+END {
+  for (i = 1; i < 4; i += 1) {
+    for (j = 1; j < 4; j += 1) {
+      table[i, j] = i * j
+    }
+  }
+
+  print "2 x 3 is " table[2, 3]
+
+  if ((3, 3) in table) {
+    print "(3, 3) is in the table"
+  }
+
+  if ((4, 1) in table) {
+    print "(4, 1) is in the table"
+  }
+
+  # Subscripts are joined by SUBSEP
+  if (("1" SUBSEP "2") in table) {
+    print "SUBSEP joins subscripts"
+  }
+
+  SUBSEP = ":"
+  pairs["a", "b"] = 1
+  for (key in pairs) {
+    print "Key with custom SUBSEP: " key
+  }
+
+  delete table[1, 1]
+  if ((1, 1) in table) {
+    print "(1, 1) was not deleted"
+  }
+}
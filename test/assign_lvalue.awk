@@ -0,0 +1,50 @@
+BEGIN {
+  OFS = "-"
+}
+
+# Assigning to a field rebuilds the record using OFS
+NR == 2 {
+  $2 = "Celsius"
+  print $0
+}
+
+# Assigning past the last field adds empty fields
+NR == 3 {
+  $4 = "extra"
+  print $0 " has " NF " fields"
+}
+
+# Assigning to NF truncates or extends the record
+NR == 4 {
+  NF = 1
+  print $0 " has " NF " fields"
+
+  NF = 3
+  print $0 " has " NF " fields"
+}
+
+# Assigning to $0 re-splits the record
+NR == 5 {
+  $0 = "a b c d"
+  print "$3 is " $3 ", NF is " NF
+}
+
+END {
+  x = 10
+  x -= 3
+  print "x -= 3 gives " x
+  x *= 4
+  print "x *= 4 gives " x
+  x /= 8
+  print "x /= 8 gives " x
+  x %= 2
+  print "x %= 2 gives " x
+
+  arr["k"] = 2
+  arr["k"] *= 5
+  print "Array element *= 5 gives " arr["k"]
+
+  # Assignment is right-associative
+  a = b = c = 7
+  print "a, b and c are " a ", " b " and " c
+}
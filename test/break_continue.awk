@@ -0,0 +1,31 @@
+END {
+  for (i = 0; i < 10; i++) {
+    if (i == 2) continue
+    if (i == 5) break
+    print "for", i
+  }
+  print "after for", i
+
+  j = 0
+  while (j < 10) {
+    j++
+    if (j % 2 == 0) continue
+    if (j > 6) break
+    print "while", j
+  }
+  print "after while", j
+
+  arr["a"] = 1; arr["b"] = 2; arr["c"] = 3
+  for (key in arr) {
+    if (key == "a") continue
+    print "for in", key
+    break
+  }
+
+  for (i = 0; i < 3; i++) {
+    for (j = 0; j < 3; j++) {
+      if (j == 1) break
+      print "nested", i, j
+    }
+  }
+}
@@ -0,0 +1,8 @@
+# Run with --csv, so quoted fields may contain commas, quotes and newlines, and printed fields are
+# quoted when needed
+NR > 1 {
+  print NR ": " NF " fields, " $1
+  print $2, $3
+  $3 = $3 + 1
+  print
+}
@@ -0,0 +1,17 @@
+# Deeply nested parentheses, subscripts and column numbers should all parse quickly
+BEGIN {
+  print ((((((((((((((((((((((((((((((((((((((((1 + 2)))))))))))))))))))))))))))))))))))))))) * 3
+
+  grid[1, 2] = "x"
+  print (((((((((((((((((((((1)))))))))))))))))))), ((((((((((((((((((((2))))))))))))))))))))) in grid
+  print (((((((((((((((((((((1, 2) in grid))))))))))))))))))))
+
+  # The same goes for nested subscripts and column numbers, which could also be lvalues
+  counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[0]]]]]]]]]]]]]]]]]]]]++
+  print counts[0], counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[counts[0]]]]]]]]]]]]]]]]]]]]
+}
+
+NR == 1 {
+  $0 = "1 2"
+  print $($($($($($($($($($($($($($($($($($($($(1))))))))))))))))))))
+}
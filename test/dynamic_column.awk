@@ -0,0 +1,31 @@
+{
+  print "Last column: " $NF
+}
+
+NR == 1 {
+  $0 = "a b c d"
+
+  # Print the columns in reverse
+  for (i = NF; i > 0; i--) {
+    print "Column " i ": " $i
+  }
+
+  i = 1
+  print "Column after i: " $(i + 1)
+  print "Column before the last: " $(NF - 1)
+
+  # $ binds tighter than ++, so this increments the column
+  n = 2
+  $n++
+  print "After $n++: " $0 ", n is still " n
+
+  # Columns can reference other columns
+  $0 = "3 x y"
+  print "$$1 is " $$1
+
+  # Column numbers are truncated to integers
+  print "$1.9 is " $(1.9)
+
+  $(NF + 2) = "new"
+  print "After assigning past the end: " $0
+}
@@ -0,0 +1,21 @@
+BEGIN {
+  celsius = "C$"
+  pattern = "^2[0-9]\\."
+}
+
+$0 ~ celsius && $1 ~ pattern {
+  print "warm celsius:", $1
+}
+
+$2 !~ "^[CF]$" {
+  print "not a unit:", $2
+}
+
+# A dot is a regex metacharacter, not a literal dot
+$1 ~ ("7" ".") {
+  print "dot:", $1
+}
+
+END {
+  print ("a/b" ~ "a\/b"), ("a.b" ~ "a\\.b"), ("axb" ~ "a\\.b")
+}
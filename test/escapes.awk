@@ -0,0 +1,12 @@
+END {
+  print "tab:\t|newline:\n|quote:\"|backslash:\\|slash:\/|octal:\101\102\0613"
+  printf "%s\n", "printf with a newline"
+  print ("\a\b\f\v\r" == sprintf("%c%c%c%c%c", 7, 8, 12, 11, 13))
+  print "unknown: \q"
+
+  path = "a/b/c.txt"
+  if (path ~ /\/c\.txt$/) print "regex with an escaped slash"
+  if ("say \"hi\"" ~ /\"hi\"/) print "regex with an escaped quote"
+  if ("A" ~ /\101/) print "regex with an octal escape"
+  if ("x\ty" ~ /x\ty/) print "regex with a tab"
+}
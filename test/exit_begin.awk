@@ -0,0 +1,13 @@
+BEGIN {
+  print "begin"
+  exit
+  print "unreachable"
+}
+
+{
+  print "unreachable"
+}
+
+END {
+  print "end", NR
+}
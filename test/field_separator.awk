@@ -0,0 +1,36 @@
+BEGIN {
+    FS = "\t"
+
+    n = split("a:b::c", parts, ":")
+    print n, parts[1], parts[3] == "", parts[4]
+    n = split("  leading and   trailing  ", parts, " ")
+    print n, parts[1], parts[3]
+    n = split("one1two22three", parts, "[0-9]+")
+    print n, parts[2], parts[3]
+    n = split("x.y.z", parts, ".")
+    print n, parts[3]
+    n = split("tab\tseparated", parts)
+    print n, parts[2]
+}
+
+NR <= 2 {
+    print NF ": " $2 " " $1
+}
+
+# A new FS only applies from the next record onwards
+NR == 3 {
+    FS = "[.\t]"
+    print NF, $1
+}
+
+NR == 4 || NR == 5 {
+    print NF ": " $1 " " $2 " " $3
+}
+
+NR == 6 {
+    FS = "1"
+}
+
+NR == 7 {
+    print NF
+}
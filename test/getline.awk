@@ -0,0 +1,46 @@
+NR == 1 {
+  # Plain getline replaces the current record
+  print "before:", $0, NF, NR, FNR
+  result = getline
+  print "after:", $0, NF, NR, FNR, result
+}
+
+NR == 3 {
+  result = getline line
+  print "var:", line, $0, NR, FNR, result
+}
+
+NR == 5 {
+  file = "test/short_data.txt"
+
+  while ((getline < file) > 0) {
+    print "file:", $0, NF, NR
+  }
+
+  print "eof:", (getline < file)
+  close(file)
+
+  getline first_line < file
+  print "file var:", first_line, $0, NR
+  close(file)
+
+  print "missing:", getline < "test/does_not_exist.txt"
+}
+
+NR == 6 {
+  command = "echo one two; echo three"
+
+  while (command | getline) {
+    print "command:", $0, NF, NR
+  }
+
+  print "status:", close(command)
+
+  ("echo " "concatenated") | getline word
+  print "command var:", word, $0, NR
+}
+
+END {
+  print "end:", getline, NR
+  print "end:", $0
+}
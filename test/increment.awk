@@ -0,0 +1,31 @@
+# Count the records for each unit, incrementing array elements
+{
+  count[$2]++
+}
+
+# Increment a field, which rebuilds the record
+$2 == "C" {
+  $1++
+  print "Incremented: " $0
+}
+
+END {
+  for (unit in count) {
+    print unit ": " count[unit]
+  }
+
+  i = 5
+  print "Post-increment returns the old value: " i++
+  print "After post-increment: " i
+  print "Pre-increment returns the new value: " ++i
+  print "Post-decrement returns the old value: " i--
+  print "After post-decrement: " i
+  print "Pre-decrement returns the new value: " --i
+
+  for (j = 0; j < 3; j++) {
+    print "j is " j
+  }
+
+  # Uninitialized values start at 0
+  print "Post-increment of uninitialized variable: " uninit++
+}
@@ -0,0 +1,12 @@
+# A dynamic regex that isn't valid is an error, which stops the program after flushing its output
+NR == 1 {
+  pattern = "^f"
+  if ($0 ~ pattern) {
+    print "Matched: " $1
+  }
+
+  pattern = "("
+  if ($0 ~ pattern) {
+    print "Not reached"
+  }
+}
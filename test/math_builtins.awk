@@ -0,0 +1,28 @@
+BEGIN {
+    print int(3.9), int(-3.9), int("12abc"), int(0)
+    printf "%d %.3f\n", sqrt(16), sqrt(2)
+    printf "%d %.3f\n", exp(0), exp(1)
+    print log(1), log(exp(2))
+    print sin(0), cos(0)
+    printf "%.5f %.5f\n", atan2(0, -1), atan2(1, 1) * 4
+
+    # Numbers from rand are in [0, 1)
+    in_range = 1
+    for (i = 0; i < 1000; i++) {
+        r = rand()
+        if (r < 0 || r >= 1) in_range = 0
+    }
+    print "in range:", in_range
+
+    # Seeding again with the same value repeats the sequence
+    srand(42)
+    first = rand() " " rand() " " rand()
+    print "previous seed:", srand(42)
+    second = rand() " " rand() " " rand()
+    print "reproducible:", first == second
+
+    srand(7)
+    print "different seed:", (rand() " " rand() " " rand()) != first
+    print "seed before time:", srand()
+    print "time seed is large:", (srand() > 1000000)
+}
@@ -0,0 +1,11 @@
+# A negative column number is an error, which stops the program after flushing its output
+NR == 1 {
+  print "Before the error: " $1
+
+  # A sign right after the $ applies to the column number, so $-i is $(-i) rather than $i - 1
+  i = -1
+  print $-i
+  print $i
+
+  print "Not reached"
+}
@@ -0,0 +1,9 @@
+# Setting NF to a negative number is an error, like accessing a negative column
+NR == 1 {
+  NF = 1
+  print "After NF = 1: " $0
+
+  n = 2
+  NF -= n
+  print "Not reached"
+}
@@ -0,0 +1,36 @@
+function skip_record() {
+  next
+}
+
+function stop(code) {
+  exit code
+  print "unreachable"
+}
+
+NR == 2 {
+  next
+}
+
+NR == 4 {
+  skip_record()
+  print "unreachable"
+}
+
+$2 == "F" && NR > 6 {
+  print "stopping at", NR
+  x = stop(3) "unassigned"
+}
+
+{
+  print NR, $1
+}
+
+END {
+  print "end", NR, x
+  exit
+  print "unreachable"
+}
+
+END {
+  print "unreachable"
+}
@@ -0,0 +1,12 @@
+# There's only one input file, so nextfile ends the input, and FNR is always the same as NR
+NR == 3 {
+  nextfile
+}
+
+{
+  print NR, FNR, $0
+}
+
+END {
+  print "end", NR, FNR
+}
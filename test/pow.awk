@@ -0,0 +1,22 @@
+# Computes the root mean square of the temperatures
+$2 == "C" || $2 == "F" {
+  sum_squares += $1 ^ 2
+  count += 1
+}
+
+END {
+  print "RMS: " (sum_squares / count) ^ 0.5
+
+  print "2 ^ 10 = " 2 ^ 10
+  print "2 ** 3 = " 2 ** 3
+  print "Right-associative: 2 ^ 3 ^ 2 = " 2 ^ 3 ^ 2
+  print "Tighter than unary minus: -2 ^ 2 = " (-2 ^ 2)
+  print "Negative exponent: 2 ^ -1 = " 2 ^ -1
+  print "Tighter than multiplication: 3 * 2 ^ 2 = " 3 * 2 ^ 2
+
+  x = 3
+  x ^= 2
+  print "x ^= 2 gives " x
+  x **= 0.5
+  print "x **= 0.5 gives " x
+}
@@ -0,0 +1,29 @@
+NR == 1 {
+  print $1, $2
+  print($1, $2, NR)
+  print ($1)($2)
+  print
+}
+
+NR == 2 {
+  OFS = "-"
+  print $1, $2, $3
+  $1 = $1
+  print
+}
+
+NR == 3 {
+  ORS = "|"
+  print "a", "b"
+  print
+}
+
+NR == 3
+
+END {
+  ORS = "."
+  OFS = ":"
+  print NR, "done"
+  ORS = sprintf("%c", 10)
+  print ""
+}
@@ -0,0 +1,14 @@
+END {
+  printf "%d|%i|%5d|%-5d|%05d|%+d|% d|%.3d|%.0d|%c", 42.9, -42.9, 42, 42, -42, 42, 42, 7, 0, 10
+  printf "%o|%#o|%x|%#x|%X|%#X|%u|%c", 8, 8, 255, 255, 255, 255, 3, 10
+  printf "%c|%c|%5c|%c", 65, "hello", "z", 10
+  printf "%s|%10s|%-10s|%.2s|%*s|%-*s|%.*s|%c", "abc", "abc", "abc", "abc", 6, "ab", 6, "ab", 1, "xyz", 10
+  printf "%e|%E|%.2e|%.0e|%#.0e|%12.3e|%-12.3e|%+e|%c", 1234.5678, 0.00012345, 1234.5678, 5, 5, -1234.5678, 3, 1, 10
+  printf "%f|%.2f|%.0f|%#.0f|%10.3f|%-10.3f|%010.2f|%+.1f|% .1f|%c", 3.14159, 2.675, 2.5, 2, 3.14159, 3.14159, -3.14159, 2, 2, 10
+  printf "%g|%G|%g|%g|%g|%g|%.3g|%#g|%g|%g|%.0g|%c", 100000, 0.00001, 1234567, 0.0001, 123.456, 0, 3.14159, 1.5, 2^100, 0.00001234, 123, 10
+}
+
+{
+  line = sprintf("%-8s|%3d|%s", $1, NR, "%")
+  print line
+}
@@ -0,0 +1,20 @@
+/F$/, /C$/ {
+  print "F to C:", NR, $0
+}
+
+# A range can start and end on the same record
+NR == 5, NR == 5 {
+  print "single:", NR
+}
+
+/temp/,
+/23/ {
+  print "header:", NR
+}
+
+# A range that never ends continues to the last record
+NR == 12, 0 {
+  print "unterminated:", NR
+}
+
+NR == 3, NR == 4
@@ -0,0 +1,19 @@
+# Blank lines separate records, and newlines also separate fields
+BEGIN {
+    RS = ""
+    FS = ": "
+}
+
+RS == "" {
+    print NR ": " NF " fields, " $2 " speaks " $4
+    print "terminator newlines:", length(RT)
+}
+
+RS == "\n" {
+    print NR ": " NF " fields, " $0
+}
+
+# The new RS takes effect from the next record
+NR == 2 {
+    RS = "\n"
+}
@@ -0,0 +1,32 @@
+BEGIN {
+  file = "target/redirect_test.txt"
+}
+
+{
+  print $1, NR | "LC_ALL=C sort -r"
+  print "first" > file
+  print $2 > file
+  printf("%s-%s", $1, "x") >> file
+  print "" >> file
+}
+
+END {
+  print "before sort"
+  print close("LC_ALL=C sort -r")
+  print "after sort"
+  print close("not open")
+  print fflush()
+
+  close(file)
+  print "appended" >> file
+  close(file)
+
+  # Read the file back using a command
+  printf "" | "cat " file
+  print close("cat " file)
+  print "exit" | "exit 3"
+  print close("exit 3")
+
+  print "to stdout" > "/dev/stdout"
+  print (2 > 1), (1 > 2)
+}
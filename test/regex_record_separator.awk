@@ -0,0 +1,8 @@
+BEGIN {
+    RS = "\t[CF]\n"
+}
+
+{
+    scale = (RT ~ /C/) ? "celsius" : "fahrenheit"
+    print NR, NF, $NF, scale
+}
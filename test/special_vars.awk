@@ -0,0 +1,43 @@
+BEGIN {
+    print "FS=[" FS "] OFS=[" OFS "] NR=" NR " FNR=" FNR
+    printf "ORS=%d RS=%d SUBSEP=%d\n", length(ORS), length(RS), length(SUBSEP)
+    print "CONVFMT=" CONVFMT, "OFMT=" OFMT
+    FS = "\t"
+    OFS = "-"
+}
+
+NR <= 3 {
+    print NR, FNR, NF, $1, $2
+    $1 = $1
+    print
+}
+
+NR == 4 {
+    arr[1, 2] = "x"
+    SUBSEP = ":"
+    arr[3, 4] = "y"
+    colons = 0
+    for (key in arr) {
+        colons += (key ~ /:/)
+    }
+    print "keys with colons", colons, ((1, 2) in arr), ((3, 4) in arr)
+
+    # CONVFMT is used when converting numbers to strings, OFMT when printing them
+    CONVFMT = "%.2f"
+    OFMT = "%.3f"
+    pi = 3.14159265
+    print pi, pi "", 17 "", 1 / 3
+    list[pi] = 1
+    for (key in list) {
+        print "subscript", key
+    }
+
+    ORS = "|\n"
+    print "custom ORS"
+    ORS = "\n"
+}
+
+END {
+    OFS = " "
+    print "records", NR, FNR, (FILENAME ~ /temperature\.txt$/)
+}
@@ -0,0 +1,7 @@
+# split can only store its pieces in an array, so anything else is an error
+BEGIN {
+  print split("a b c", pieces), pieces[3]
+
+  n = split("a b c", pieces[1])
+  print "Not reached"
+}
@@ -0,0 +1,30 @@
+{
+  print length, length(), length($1), length ($2)
+}
+
+END {
+  s = "hello"
+  print substr(s, 2), substr(s, 2, 3), substr(s, 0), substr(s, -1, 3), substr(s, 0, 2)
+  print "[" substr(s, 10) "]", "[" substr(s, 2, -1) "]", "[" substr(s, 2, 0) "]", substr(s, 1.4, 2.6)
+  print substr(12345, 2, 2), substr(s, 5, 100)
+
+  print index(s, "l"), index(s, "lo"), index(s, "z"), index("", "a"), index("a.b", ".")
+
+  n = split("a b  c ", parts)
+  print n, parts[1], parts[2], parts[3]
+  n = split("2023-01-15", date, "-")
+  print n, date[1], date[2], date[3]
+  n = split("a1b22c333d", pieces, /[0-9]+/)
+  print n, pieces[1], pieces[4]
+  n = split("x, y,z", items, ", *")
+  print n, items[1], items[2], items[3]
+  n = split("a.b", dots, ".")
+  print n, dots[1], dots[2]
+  n = split("", empty)
+  print n, length(empty)
+  split("one two three", words)
+  print length(words)
+
+  print tolower("MiXeD 123"), toupper("MiXeD 123")
+  print length("four") length "!"
+}
@@ -0,0 +1,42 @@
+NR > 1 {
+  sub(/C$/, "Celsius")
+  sub(/F$/, "Fahrenheit")
+  print $0
+}
+
+END {
+  s = "banana"
+  print gsub(/an/, "[&]", s), s
+  t = "banana"
+  print sub("a", "\\&", t), t
+  u = "a.b.c"
+  print gsub(".", "-", u), u
+  v = "a.b.c"
+  print gsub(/\./, "\\\\", v), v
+  w = "abc"
+  print gsub(/x*/, "-", w), w
+  x = "abc"
+  print gsub(/b*/, "-", x), x
+  y = "no match"
+  print sub(/z/, "!", y), y
+
+  arr["k"] = "hello world"
+  gsub(/o/, "0", arr["k"])
+  print arr["k"]
+
+  $0 = "one two three"
+  gsub(/o/, "O", $2)
+  print $0, NF
+
+  print match("foobar", /o+/), RSTART, RLENGTH
+  print match("foobar", "z"), RSTART, RLENGTH
+  print match("xyz", /$/), RSTART, RLENGTH
+
+  # match sets RSTART like an assignment would, so a parameter with that name hides the global
+  print match_start(9), RSTART
+}
+
+function match_start(RSTART) {
+  match("abc", /c/)
+  return RSTART
+}
@@ -0,0 +1,16 @@
+{
+  print $1 " is " ($2 == "C" ? "Celsius" : $2 == "F" ? "Fahrenheit" : "a header")
+}
+
+END {
+  x = "x is unmodified."
+  y = "y is unmodified."
+
+  1 ? (x = "x has been modified.") : (y = "y has been modified.")
+
+  print x
+  print y
+
+  max = 3 > 7 ? 3 : 7
+  print "The max of 3 and 7 is " max
+}
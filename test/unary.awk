@@ -0,0 +1,24 @@
+# Print each unit only the first time it's seen
+!seen[$2] {
+  seen[$2] = 1
+  print "First time seeing " $2
+}
+
+END {
+  x = 5
+  # Like in awk, `"text" -x` would be a subtraction, so we use parentheses
+  print "Negated x: " (-x)
+  print "Double negation: " (- -x)
+  print "Unary minus binds tighter than multiplication: " (-x * 2)
+  print "Subtracting a negative: " 3 - -x
+
+  # Unary plus converts strings to numbers
+  print "Coerced: " (+"3.5kg")
+  print "Coerced exponent: " (+" 2e2")
+  print "Coerced non-number: " (+"kg")
+
+  print "Not zero: " !0
+  print "Not a string: " !"text"
+  print "Not an empty string: " !""
+  print "Double not: " !!5
+}